@@ -0,0 +1,131 @@
+//! Ablative heat shields, as KSP's `ModuleAblator` models them: the resin of the virgin material
+//! pyrolyses into gas above a temperature, carries heat away & leaves the char behind.
+//!
+//! The pyrolysable mass becomes the ablative resource, one unit per [`ABLATOR_UNIT_MASS`]. Its loss rate
+//! `exp(lossExp / T)` rises [`LOSS_RISE`] fold from the pyrolysis temperature to `skinMaxTemp`.
+
+use super::data_holder::*;
+use super::property_table::PropertyTable;
+
+/// kg per unit of the ablative resource
+pub const ABLATOR_UNIT_MASS: f32 = 1.0;
+/// Factor the loss rate rises by from the pyrolysis temperature to the max temperature
+pub const LOSS_RISE: f32 = 100.0;
+/// K above the pyrolysis temperature at which a layer has the properties of its char
+pub const CHAR_TRANSITION: f32 = 10.0;
+
+/// Ablative properties of a material, the virgin ones are its table & density
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Ablator {
+    /// kg/m³
+    pub char_density: f32,
+    /// cp, k (in the `R_th` field) & emissivity of the char
+    pub char_data: Data,
+    /// J/kg of pyrolysed mass
+    pub heat_of_ablation: f32,
+    /// K
+    pub pyrolysis_temp: f32,
+}
+
+impl Ablator {
+    /// Mass per area the layer loses until it is charred through, kg/m²
+    pub fn pyrolysable_mass(&self, segment: &Segment) -> f32 {
+        (segment.density - self.char_density).max(0.0) * segment.tickness * segment.portion
+    }
+
+    /// Material table with the char properties from [`CHAR_TRANSITION`] above the pyrolysis temperature on,
+    /// empty cells of the `Char` row keep the virgin value at the pyrolysis temperature. Without `Char` row the table stays.
+    pub fn charred(&self, table: &PropertyTable<Data>) -> PropertyTable<Data> {
+        let Ok(virgin) = table.at(self.pyrolysis_temp) else { return table.clone() };
+        if self.char_data == Data::default() {
+            return table.clone();
+        }
+        let or_virgin = |char: f32, virgin: f32| if char == 0.0 { virgin } else { char };
+        let char_data = Data {
+            cp: or_virgin(self.char_data.cp, virgin.cp),
+            R_th: or_virgin(self.char_data.R_th, virgin.R_th),
+            e: or_virgin(self.char_data.e, virgin.e),
+        };
+        let char_temp = self.pyrolysis_temp + CHAR_TRANSITION;
+        // held up to the last row, so that the extrapolation above it stays at the char
+        let last_temp = table.temps().last().map_or(char_temp, |temp| temp.max(char_temp));
+
+        let mut rows = table.iter()
+            .filter(|(temp, _data)| *temp < self.pyrolysis_temp)
+            .map(|(temp, data)| (temp, *data))
+            .collect::<Vec<(f32, Data)>>();
+        rows.extend([(self.pyrolysis_temp, virgin), (char_temp, char_data)]);
+        if last_temp > char_temp {
+            rows.push((last_temp, char_data));
+        }
+        rows.into_iter().collect()
+    }
+}
+
+/// `ModuleAblator` values of a TPS or part
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AblatorParams {
+    /// units/m² of the Min & Max section
+    pub resource_per_area_min: f32,
+    pub resource_per_area_max: f32,
+    /// K, `ablationTempThresh`, pyrolysis temperature of the first ablator layer
+    pub temp_threshold: f32,
+    pub loss_exp: f32,
+    /// kJ per unit
+    pub pyrolysis_loss_factor: f32,
+}
+
+impl AblatorParams {
+    /// Values of a computed TPS, `None` without ablator layers
+    pub fn from_tps(tps: &TPS) -> Option<Self> {
+        let ablators = |segments: &[Segment]| segments.iter()
+            .filter_map(|segment| segment.ablator.map(|ablator| (ablator, ablator.pyrolysable_mass(segment))))
+            .collect::<Vec<(Ablator, f32)>>();
+        let (min, max) = (ablators(&tps.segments_min), ablators(&tps.segments_max));
+        let layers = if min.is_empty() { &max } else { &min };
+        let (first, _) = layers.first()?;
+
+        let mass = layers.iter().map(|(_, mass)| mass).sum::<f32>();
+        let heat = layers.iter().map(|(ablator, mass)| ablator.heat_of_ablation * mass).sum::<f32>();
+        Some(AblatorParams {
+            resource_per_area_min: min.iter().map(|(_, mass)| mass).sum::<f32>() / ABLATOR_UNIT_MASS,
+            resource_per_area_max: max.iter().map(|(_, mass)| mass).sum::<f32>() / ABLATOR_UNIT_MASS,
+            temp_threshold: first.pyrolysis_temp,
+            loss_exp: loss_exp(first.pyrolysis_temp, tps.temp),
+            pyrolysis_loss_factor: if mass > 0.0 { heat / mass * ABLATOR_UNIT_MASS / 1000.0 } else { 0.0 },
+        })
+    }
+
+    /// Values of a part from its TPS with their portions, `None` if none of them ablates
+    pub fn from_parts(parts: &[(AblatorParams, f32)]) -> Option<Self> {
+        let resource = parts.iter().map(|(params, portion)| params.resource_per_area_min * portion).sum::<f32>();
+        let weight = |params: &AblatorParams, portion: f32| if resource > 0.0 { params.resource_per_area_min * portion / resource } else { 1.0 / parts.len() as f32 };
+        Some(AblatorParams {
+            resource_per_area_min: resource,
+            resource_per_area_max: parts.iter().map(|(params, portion)| params.resource_per_area_max * portion).sum(),
+            temp_threshold: parts.iter().map(|(params, _)| params.temp_threshold).reduce(f32::min)?,
+            loss_exp: parts.iter().map(|(params, portion)| params.loss_exp * weight(params, *portion)).sum(),
+            pyrolysis_loss_factor: parts.iter().map(|(params, portion)| params.pyrolysis_loss_factor * weight(params, *portion)).sum(),
+        })
+    }
+
+    /// Follow a height change of `tps_ref` to new areal densities, the resource scales like the areal density
+    pub fn resize(&mut self, tps_ref: &TPS, areal_density_min: f32, areal_density_max: f32) {
+        let span = tps_ref.areal_density_max - tps_ref.areal_density_min;
+        let resource = |areal_density: f32| {
+            let factor = if span != 0.0 { (areal_density - tps_ref.areal_density_min) / span } else { 0.0 };
+            self.resource_per_area_min + (self.resource_per_area_max - self.resource_per_area_min) * factor
+        };
+        (self.resource_per_area_min, self.resource_per_area_max) = (resource(areal_density_min), resource(areal_density_max));
+    }
+
+    /// Mass per area left once all resource is gone, kg/m²
+    pub fn reentry_mass(areal_density: f32, resource_per_area: f32) -> f32 {
+        areal_density - resource_per_area * ABLATOR_UNIT_MASS
+    }
+}
+
+/// `lossExp` that makes the loss rate rise [`LOSS_RISE`] fold from `pyrolysis_temp` to `temp_max`
+pub fn loss_exp(pyrolysis_temp: f32, temp_max: f32) -> f32 {
+    LOSS_RISE.ln() / (1.0 / temp_max - 1.0 / pyrolysis_temp)
+}
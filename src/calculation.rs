@@ -0,0 +1,313 @@
+use super::data_holder::*;
+use super::error::CalcError;
+use super::interpolation::*;
+use super::property_table::PropertyTable;
+
+pub const TEMPERATURE_EQUALIZED: f32 = 0.0;//273.15;
+
+/// Run a single layer through the pipeline: fill csv gaps, map onto the TPS temperature range,
+/// adjust to thickness and average across the layer. Ablators switch to their char above the pyrolysis temperature.
+pub fn calc_segment(segment: &mut Segment, tps_temp: f32, temp_grid: &TempGrid) {
+    fill_gaps_in_csv(&mut segment.data_csv, segment.interpolation);
+    if let Some(ablator) = segment.ablator {
+        segment.data_csv = ablator.charred(&segment.data_csv);
+    }
+    segment.areal_density = (segment.density * segment.tickness + segment.additive_areal_weight) * segment.portion ;
+
+    segment.data_tps_temp_map = map_component_data_to_assembly(tps_temp, segment.temp_hot_side, &segment.data_csv, segment.interpolation, &temp_grid.temps);
+    segment.data_height_adjust = adjust_to_height(segment.tickness * segment.portion, &segment.data_tps_temp_map);
+    segment.data_avg_r = avg_cp_k(segment.tickness, &segment.data_height_adjust, segment.temp_hot_side, segment.temp_cold_side, segment.interpolation, &temp_grid.temps_fine);
+}
+
+/// Fill empty cells (read as 0.0) of a material table column by column with its scheme, see [`fill_gaps_with`]
+pub fn fill_gaps_in_csv(thermal_list: &mut PropertyTable<Data>, interpolation: Interpolation) {
+    let temps = thermal_list.temps().to_vec();
+    let mut cp = thermal_list.values().iter().map(|data| data.cp).collect::<Vec<f32>>();
+    let mut r_th = thermal_list.values().iter().map(|data| data.R_th).collect::<Vec<f32>>();
+    let mut e = thermal_list.values().iter().map(|data| data.e).collect::<Vec<f32>>();
+
+    fill_gaps_with(interpolation.specific_heat, &temps, &mut cp);
+    fill_gaps_with(interpolation.conductivity, &temps, &mut r_th);
+    fill_gaps_with(interpolation.emissivity, &temps, &mut e);
+
+    for (i, data) in thermal_list.values_mut().iter_mut().enumerate() {
+        *data = Data { cp: cp[i], R_th: r_th[i], e: e[i] };
+    }
+}
+
+/// Adjust read values to thickness & density
+pub fn adjust_to_height(height: f32, data: &PropertyTable<LayerData>) -> PropertyTable<LayerData> {
+    data.map(|_temp, row| {
+        let mut row = *row;
+        if row.thermal_data.R_th != 0.0 {
+            row.thermal_data.R_th = height / row.thermal_data.R_th * 1000.0;
+        }
+        row
+    })
+}
+
+/// expand list in to predefined range & steps and fill in the gaps.
+///
+/// Below the first row its values are held, the first step above the last row is extrapolated
+/// from the last two rows & held from there on. An empty list stays empty. `temp_sub_part` keeps its ratio to `temp_part` outside the rows.
+/// Between the rows the thermal data follows the scheme of its column, `temp_sub_part` is linear.
+pub fn fit_list(thermal_list: &PropertyTable<LayerData>, interpolation: Interpolation, ref_temp_list: &[f32]) -> PropertyTable<LayerData> {
+    if thermal_list.is_empty() {
+        return PropertyTable::default();
+    }
+    let temps = thermal_list.temps();
+    let values = thermal_list.values();
+    let n = thermal_list.len();
+    let columns = [
+        (interpolation.specific_heat, values.iter().map(|row| row.thermal_data.cp).collect::<Vec<f32>>()),
+        (interpolation.conductivity, values.iter().map(|row| row.thermal_data.R_th).collect::<Vec<f32>>()),
+        (interpolation.emissivity, values.iter().map(|row| row.thermal_data.e).collect::<Vec<f32>>()),
+    ].map(|(scheme, ys)| (scheme, scheme.slopes(temps, &ys), ys));
+    let mut data_adjusted = Vec::<(f32, LayerData)>::with_capacity(ref_temp_list.len());
+
+    for temp in ref_temp_list.iter() {
+        let row = match (thermal_list.position(*temp), data_adjusted.last()) {
+            (Position::Below, _) => {
+                let first = values[0];
+                LayerData { temp_sub_part: first.temp_sub_part * *temp / temps[0], ..first }
+            },
+            (Position::At(i), _) => values[i],
+            (Position::Between(i), _) => {
+                let [cp, r_th, e] = columns.each_ref().map(|(scheme, slopes, ys)| scheme.segment(temps, ys, slopes, i, *temp));
+                LayerData {
+                    thermal_data: Data { cp, R_th: r_th, e },
+                    temp_sub_part: lerp(temps[i], values[i].temp_sub_part, temps[i + 1], values[i + 1].temp_sub_part, *temp),
+                }
+            },
+            (Position::Above, Some((last_temp, last))) if *last_temp >= temps[n - 1] => {
+                LayerData { temp_sub_part: last.temp_sub_part / last_temp * temp, ..*last }
+            },
+            (Position::Above, _) if n > 1 => lerp(temps[n - 2], values[n - 2], temps[n - 1], values[n - 1], *temp),
+            (Position::Above, _) => values[0],
+        };
+        data_adjusted.push((*temp, row));
+    }
+
+    data_adjusted.into_iter().collect()
+}
+
+/// calculate the part values based on data from its structures
+pub fn calculate_part(part: &mut Part, temp_ref_list: &[f32]) -> Result<(), CalcError> {
+    part.areal_density_min = 0.0;
+    part.areal_density_max = 0.0;
+    part.height_min = 0.0;
+    part.height_max = 0.0;
+    part.cost_per_area_min = 0.0;
+    part.cost_per_area_max = 0.0;
+    // in f64, so TPS of equal absorptance give exactly theirs
+    let mut portions = 0.0_f64;
+    let mut absorbation = 0.0_f64;
+
+    for (tps, portion, _data_min, _data_max) in part.tps_list.iter() {
+        part.areal_density_min += tps.areal_density_min * portion;
+        part.areal_density_max += tps.areal_density_max * portion;
+        part.cost_per_area_min += tps.cost_per_area_min * portion;
+        part.cost_per_area_max += tps.cost_per_area_max * portion;
+        portions += *portion as f64;
+        absorbation += tps.absorbation_const.unwrap_or(0.0) as f64 * *portion as f64;
+
+        part.height_min += tps.tickness_min * portion;
+        part.height_max += tps.tickness_max * portion;
+    }
+
+    if portions > 0.0 {
+        part.absorbation_const = (absorbation / portions) as f32;
+    }
+    // the CostPerArea row stands in if none of the TPS has prices
+    if part.tps_list.iter().all(|(tps, ..)| tps.cost_per_area_min == 0.0 && tps.cost_per_area_max == 0.0) {
+        part.cost_per_area_min = part.cost_per_area;
+        part.cost_per_area_max = part.cost_per_area;
+    }
+
+    let zero = temp_ref_list.iter().map(|temp| (*temp, Data::default())).collect::<PropertyTable<Data>>();
+    let (mut sum_min, mut sum_max) = (zero.clone(), zero);
+    for (tps, portion, data_min, data_max) in part.tps_list.iter() {
+        sum_min = (&sum_min + &part_share(data_min, tps.areal_density_min / part.areal_density_min, *portion, temp_ref_list)?)?;
+        sum_max = (&sum_max + &part_share(data_max, tps.areal_density_max / part.areal_density_max, *portion, temp_ref_list)?)?;
+    }
+    part.data_min = sum_min.iter().map(|(temp, sum)| (temp - 25.0, Data { R_th: 1.0 / sum.R_th, ..*sum })).collect();
+    part.data_max = sum_max.iter().map(|(temp, sum)| (temp - 25.0, Data { R_th: 1.0 / sum.R_th, ..*sum })).collect();
+    Ok(())
+}
+
+/// Values a TPS adds to its part at the temperatures of `temp_list`, cp weighted by its share of the areal density
+fn part_share(data: &PropertyTable<LayerData>, density_frac: f32, portion: f32, temp_list: &[f32]) -> Result<PropertyTable<Data>, CalcError> {
+    Ok(data.resample(temp_list)?.map(|temp, row| Data {
+        cp: row.thermal_data.cp * density_frac * portion * (row.temp_sub_part - TEMPERATURE_EQUALIZED) / (temp - TEMPERATURE_EQUALIZED),
+        R_th: portion * row.thermal_data.R_th,
+        e: row.thermal_data.e * portion,
+    }))
+}
+
+/// calculate the structure values based on data from layer
+pub fn calc_tps_height_density(tps: &mut TPS) {
+    tps.areal_density_min = 0.0;
+    tps.tickness_min = 0.0;
+    tps.areal_density_max = 0.0;
+    tps.tickness_max = 0.0;
+    tps.cost_per_area_min = 0.0;
+    tps.cost_per_area_max = 0.0;
+    
+    for layer in tps.segments_min.iter() {
+        tps.areal_density_min += layer.areal_density; 
+        tps.tickness_min += layer.tickness;
+        tps.cost_per_area_min += layer.cost.map_or(0.0, |cost| cost.per_area(layer)) * tps.manufacturing_factor;
+    }
+
+    for layer in tps.segments_max.iter() {
+        tps.areal_density_max += layer.areal_density; 
+        tps.tickness_max += layer.tickness;
+        tps.cost_per_area_max += layer.cost.map_or(0.0, |cost| cost.per_area(layer)) * tps.manufacturing_factor;
+    }
+}
+
+/// Sum of the layers at the temperatures of `temp_list`, the emissivity of the first layer that has one.
+/// Layers without data add nothing
+pub fn calc_tps_data(segments: &[Segment], temp_list: &[f32]) -> PropertyTable<Data> {
+    temp_list.iter()
+        .map(|temp| {
+            let rows = segments.iter()
+                .filter_map(|layer| layer.data_tps_temp_mult.at(*temp).ok())
+                .collect::<Vec<LayerData>>();
+            let cp = rows.iter().map(|row| row.thermal_data.cp).sum();
+            let r_th = rows.iter().map(|row| row.thermal_data.R_th).sum();
+            let e = rows.iter().map(|row| row.thermal_data.e).find(|e| *e > 0.0).unwrap_or(0.0);
+            (*temp, Data { cp, R_th: r_th, e })
+        })
+        .collect()
+}
+
+
+/// multiplyer on component values based om assembly temperature & density
+pub fn tps_value_mult(assembly_density: f32, segment_density: f32, segment_data: &PropertyTable<LayerData>) -> PropertyTable<LayerData> {
+    let density_frac = segment_density / assembly_density;
+
+    segment_data.map(|temp, row| LayerData {
+        temp_sub_part: row.temp_sub_part,
+        thermal_data: Data{cp: row.thermal_data.cp * row.temp_sub_part * density_frac / temp,
+                           R_th: row.thermal_data.R_th, //prop_list.thermal_data.R_th *= prop_list.temp_sub_part / prop_list.temp_part;
+                           e: row.thermal_data.e,
+        }})
+}
+
+pub fn map_component_data_to_assembly(assemb_temp_max: f32, comp_temp_max: f32, comp_data: &PropertyTable<Data>, interpolation: Interpolation, temp_list: &[f32]) -> PropertyTable<LayerData> {
+    let temp_mult = if comp_temp_max < assemb_temp_max {
+        (assemb_temp_max - TEMPERATURE_EQUALIZED) / (comp_temp_max - TEMPERATURE_EQUALIZED)
+    } else {
+        1.0
+    };
+
+    let data_new = comp_data.iter()
+        .map(|(temp, data)| ((temp - TEMPERATURE_EQUALIZED) * temp_mult + TEMPERATURE_EQUALIZED, LayerData{thermal_data: *data, temp_sub_part: temp}))
+        .collect::<PropertyTable<LayerData>>();
+    fit_list(&data_new, interpolation, temp_list)
+}
+
+/// Returns a new list with an averaged conductivity & insulation accross tickness, for given cold & Hot Side Temperature.
+/// The finer steps follow the `interpolation` of the material
+pub fn avg_cp_k(lenght: f32, data_ref: &PropertyTable<LayerData>, temp_max: f32, temp_min: f32, interpolation: Interpolation, temp_list_5: &[f32] ) -> PropertyTable<LayerData>{
+    if temp_min == temp_max {
+        return data_ref.clone();
+    }
+
+    let mut data_out = data_ref.clone();
+    let mut steps = Vec::<(f32,f32,f32,f32,f32)>::new();
+
+    let temp_frac = temp_min / temp_max;
+
+
+    // smaller steps for smother curve, negating the effect of missing a step due to multiplication with temp_frac
+    let data = fit_list(data_ref, interpolation, temp_list_5);    
+
+    // extrapolate d value for the rest
+    let q_ref = 1.0; // q = q1 = q2 = qi = Ti * di / ki; -> di = q * ki / Ti
+    let mut d_sum: f32 = 0.0;
+    for row in data.values().iter() {
+        let k: f32 = lenght / row.thermal_data.R_th;
+        let d = q_ref * k / row.temp_sub_part ;
+        d_sum += d;
+        steps.push((row.temp_sub_part ,k , d, d_sum, row.thermal_data.cp));
+    }
+    
+    for row in data_out.values_mut().iter_mut() {
+        let mut i = 0;
+        let mut r_th = 0.0;
+        let mut cp = 0.0;
+        let mut d_sum = 0.0;
+        while i < steps.len() {
+            if steps[i].0 >= row.temp_sub_part * temp_frac && steps[i].0 <= row.temp_sub_part {
+                r_th += steps[i].2 / steps[i].1;
+                cp += steps[i].4 * steps[i].2;
+                d_sum += steps[i].2
+            }
+            i += 1;
+        }
+
+        row.thermal_data.R_th = r_th / d_sum * lenght;
+        row.thermal_data.cp = cp / d_sum;
+    }
+    data_out
+}
+
+/// Layer temperature while the assembly is at `temp`, interpolated between the rows of `data`
+pub fn layer_temp_at(data: &PropertyTable<LayerData>, temp: f32) -> f32 {
+    data.at(temp).map_or(temp, |row| row.temp_sub_part)
+}
+
+pub fn tps_change_height(tps_ref: &TPS, new_height_min: f32, new_height_max: f32) -> TPS {
+    let mut tps = tps_ref.clone();
+
+    if new_height_min == f32::INFINITY {
+        tps.tickness_min = tps.tickness_max;
+        tps.areal_density_min = tps.areal_density_max;
+        tps.cost_per_area_min = tps.cost_per_area_max;
+
+        tps.data_min = blend_data(&tps.data_min, &tps.data_max, 1.0);
+    } else if new_height_min != f32::NEG_INFINITY {
+        let height_factor = (new_height_min - tps_ref.tickness_min) / (tps_ref.tickness_max - tps_ref.tickness_min);
+
+        if height_factor > 0.001 && height_factor < 0.999 {
+            tps.tickness_min = (tps.tickness_max - tps.tickness_min) * height_factor + tps.tickness_min;
+            tps.areal_density_min = (tps.areal_density_max - tps.areal_density_min) * height_factor + tps.areal_density_min;
+            tps.cost_per_area_min = (tps.cost_per_area_max - tps.cost_per_area_min) * height_factor + tps.cost_per_area_min;
+
+            tps.data_min = blend_data(&tps.data_min, &tps.data_max, height_factor);
+        }
+    }
+    if new_height_max == f32::NEG_INFINITY {
+        tps.tickness_max = tps.tickness_min;
+        tps.areal_density_max = tps.areal_density_min;
+        tps.cost_per_area_max = tps.cost_per_area_min;
+
+        tps.data_max = blend_data(&tps.data_max, &tps.data_min, 1.0);
+    } else if new_height_max != f32::INFINITY {
+        let height_factor = (new_height_max - tps_ref.tickness_min) / (tps_ref.tickness_max - tps_ref.tickness_min);
+
+        if height_factor < 0.999 && height_factor > 0.001 {
+            tps.tickness_max = (tps.tickness_max - tps.tickness_min) * height_factor + tps.tickness_min;
+            tps.areal_density_max = (tps.areal_density_max - tps.areal_density_min) * height_factor + tps.areal_density_min;
+            tps.cost_per_area_max = (tps.cost_per_area_max - tps.cost_per_area_min) * height_factor + tps.cost_per_area_min;
+
+            tps.data_max = blend_data(&tps.data_max, &tps.data_min, 1.0 - height_factor);
+        }
+    }
+    let (areal_density_min, areal_density_max) = (tps.areal_density_min, tps.areal_density_max);
+    if let Some(ablator) = tps.ablator.as_mut() {
+        ablator.resize(tps_ref, areal_density_min, areal_density_max);
+    }
+    tps
+}
+
+/// cp & R_th of `from` moved towards `to` by `factor`, taken at the temperatures of `from`. The emissivity stays
+fn blend_data(from: &PropertyTable<Data>, to: &PropertyTable<Data>, factor: f32) -> PropertyTable<Data> {
+    from.map(|temp, data| to.at(temp).map_or(*data, |other| Data {
+        cp: (other.cp - data.cp) * factor + data.cp,
+        R_th: (other.R_th - data.R_th) * factor + data.R_th,
+        e: data.e,
+    }))
+}
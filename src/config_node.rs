@@ -0,0 +1,107 @@
+//! Reader for KSP ConfigNode files, the format of `.cfg` presets.
+//!
+//! ```
+//! use std::path::Path;
+//! use ro_materials_csv_calculator::ConfigNode;
+//!
+//! let text = "ROThermal_PRESET // comment\n{\n    name = HRSI\n    skinMaxTemp = 1533\n}";
+//! let root = ConfigNode::parse(Path::new("HRSI.cfg"), text).unwrap();
+//! assert_eq!(root.nodes[0].type_name(), "ROThermal_PRESET");
+//! assert_eq!(root.nodes[0].get("skinMaxTemp"), Some("1533"));
+//! ```
+
+use super::error::CalcError;
+
+use std::path::Path;
+
+/// Node with its values & child nodes, names & keys are kept as written including ModuleManager operators
+#[derive(Debug, Clone, Default)]
+pub struct ConfigNode {
+    pub name: String,
+    /// line of the opening brace, 0 for the root
+    pub line: u64,
+    pub values: Vec<(String, String)>,
+    pub nodes: Vec<ConfigNode>,
+}
+
+impl ConfigNode {
+    /// Parse `text` into a root node holding the top level nodes & values, `path` is only used for errors
+    pub fn parse(path: &Path, text: &str) -> Result<ConfigNode, CalcError> {
+        let mut stack = vec![ConfigNode::default()];
+        // node name waiting for its "{"
+        let mut pending: Option<(String, u64)> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i as u64 + 1;
+            let mut rest = line.split("//").next().unwrap_or("");
+
+            loop {
+                let brace = rest.find(['{', '}']);
+                let item = rest[..brace.unwrap_or(rest.len())].trim();
+
+                if !item.is_empty() {
+                    if let Some((name, line)) = pending.take() {
+                        return Err(CalcError::config(path, line, &format!("expected '{{' after \"{}\"", name)));
+                    }
+                    match item.split_once('=') {
+                        Some((key, value)) => stack.last_mut().unwrap().values.push((key.trim().to_string(), value.trim().to_string())),
+                        None               => pending = Some((item.to_string(), line_number)),
+                    }
+                }
+
+                let Some(brace) = brace else { break };
+                if rest[brace..].starts_with('{') {
+                    let Some((name, _line)) = pending.take() else {
+                        return Err(CalcError::config(path, line_number, "'{' without node name"));
+                    };
+                    stack.push(ConfigNode { name, line: line_number, ..Default::default() });
+                } else {
+                    if let Some((name, line)) = pending.take() {
+                        return Err(CalcError::config(path, line, &format!("expected '{{' after \"{}\"", name)));
+                    }
+                    if stack.len() == 1 {
+                        return Err(CalcError::config(path, line_number, "unmatched '}'"));
+                    }
+                    let node = stack.pop().unwrap();
+                    stack.last_mut().unwrap().nodes.push(node);
+                }
+                rest = &rest[brace + 1..];
+            }
+        }
+
+        if let Some((name, line)) = pending {
+            return Err(CalcError::config(path, line, &format!("expected '{{' after \"{}\"", name)));
+        }
+        if stack.len() > 1 {
+            let node = stack.last().unwrap();
+            return Err(CalcError::config(path, node.line, &format!("\"{}\" lacks its closing '}}'", node.name)));
+        }
+        Ok(stack.pop().unwrap())
+    }
+
+    /// Node name without ModuleManager operator, `[filter]` & `:` clauses
+    pub fn type_name(&self) -> &str {
+        strip_operator(&self.name).split(['[', ':']).next().unwrap_or("").trim()
+    }
+
+    /// First value of `key`, keys of patches match without their operator, deletions (`!key = delete`) don't
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.iter()
+            .find(|(name, _value)| !name.starts_with('!') && strip_operator(name) == key)
+            .map(|(_name, value)| value.as_str())
+    }
+
+    /// All nodes below this one of type `type_name`, nested nodes included
+    pub fn find_all<'a>(&'a self, type_name: &str, found: &mut Vec<&'a ConfigNode>) {
+        for node in self.nodes.iter() {
+            if node.type_name() == type_name {
+                found.push(node);
+            }
+            node.find_all(type_name, found);
+        }
+    }
+}
+
+fn strip_operator(name: &str) -> &str {
+    name.trim_start_matches(['@', '%', '+', '$', '!', '-', '&', '|'])
+}
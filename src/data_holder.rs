@@ -1,179 +1,370 @@
-use std:: ops::*;
-
-pub struct Part {
-    pub name: String,
-    pub description: String,
-    pub temp: f32,
-    pub absorbation_const: f32,
-    pub cost_per_area: f32,
-    pub has_ablator: bool,
-    pub height_min: f32,
-    pub height_max: f32,
-    pub areal_density_min: f32,
-    pub areal_density_max: f32,
-    pub tps_list: Vec<(TPS, f32, Vec<DataTriplet>, Vec<DataTriplet>)>,
-    pub data_min: Vec<DataPair>,
-    pub data_max: Vec<DataPair>,
-}
-
-#[derive(Clone)]
-pub struct TPS {
-    pub name: String,
-    pub description: String,
-    pub temp: f32,
-    pub absorbation_const: f32,
-    pub data_min: Vec<DataPair>,
-    pub areal_density_min: f32,
-    pub tickness_min: f32,
-    pub segments_min: Vec<Segment>,
-
-    pub data_max: Vec<DataPair>,
-    pub areal_density_max: f32,
-    pub tickness_max: f32,
-    pub segments_max: Vec<Segment>,
-
-    pub has_ablator: String,
-    pub cost_per_area: f32,
-}
-impl Default for TPS{
-    fn default() -> Self {
-        TPS {
-            name: "".to_string(),
-            description: "".to_string(),
-            temp: 0.0,
-            absorbation_const: 0.0,
-            data_min: Vec::<DataPair>::new(),
-            areal_density_min: 0.0,
-            tickness_min: 0.0,
-            segments_min: Vec::<Segment>::new(),        
-            data_max: Vec::<DataPair>::new(),
-            areal_density_max: 0.0,
-            tickness_max: 0.0,
-            segments_max: Vec::<Segment>::new(),       
-            has_ablator: "false".to_string(),
-            cost_per_area: 0.0,
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Segment {
-    pub name: String,
-    pub path: String,
-    pub portion: f32,
-    pub temp_max: f32,
-    pub temp_hot_side: f32,
-    pub temp_cold_side: f32,
-    pub density: f32,
-    pub tickness: f32,
-    pub areal_density: f32,
-    pub additive_areal_weight: f32,
-    pub data_csv: Vec<DataPair>,
-    pub data_tps_temp_map: Vec<DataTriplet>,
-    pub data_height_adjust: Vec<DataTriplet>,
-    pub data_avg_r: Vec<DataTriplet>,
-    pub data_tps_temp_mult: Vec<DataTriplet>,
-}
-impl Default for Segment{
-    fn default() -> Self {
-        Segment {
-            name: "".to_string(),
-            path: "".to_string(),
-            portion: 0.0,
-            temp_max: 0.0,
-            temp_hot_side: 0.0,
-            temp_cold_side: 0.0,
-            density: 0.0,
-            tickness: 0.0,
-            areal_density: 0.0,
-            additive_areal_weight: 0.0,
-            data_csv: Vec::<DataPair>::new(),
-            data_height_adjust: Vec::<DataTriplet>::new(),
-            data_tps_temp_map: Vec::<DataTriplet>::new(),
-            data_tps_temp_mult: Vec::<DataTriplet>::new(),
-            data_avg_r: Vec::<DataTriplet>::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-#[allow(non_snake_case)]
-pub struct Data {
-    pub cp: f32,
-    pub R_th: f32,
-    pub e: f32
-}
-impl Add<Data> for Data {
-    type Output = Self;
-    fn add(self, other: Self) -> Self::Output {
-        Data{
-            cp: self.cp + other.cp,
-            R_th: self.R_th + other.R_th,
-            e: self.e + other.e,
-        }
-    }
-
-}
-impl Sub<Data> for Data {
-    type Output = Self;
-    fn sub(self, other: Self) -> Self::Output {
-        Data{
-            cp: self.cp - other.cp,
-            R_th: self.R_th - other.R_th,
-            e: self.e - other.e,
-        }
-    }
-
-}
-impl Div<Data> for Data {
-    type Output = Self;
-    fn div(self, other: Self) -> Self::Output {
-        Data{
-            cp: self.cp / other.cp,
-            R_th: self.R_th / other.R_th,
-            e: self.e / other.e,
-        }
-    }
-
-}
-impl Div<f32> for Data {
-    type Output = Self;
-    fn div(self, other: f32) -> Self::Output {
-        Data{
-            cp: self.cp / other,
-            R_th: self.R_th / other,
-            e: self.e / other,
-        }
-    }
-
-}
-impl Mul<f32> for Data {
-    type Output = Self;
-    fn mul(self, other: f32) -> Self::Output {
-        Data{
-            cp: self.cp * other,
-            R_th: self.R_th * other,
-            e: self.e * other,
-        }
-    }
-
-}
-#[derive(Debug, Clone, Copy)]
-pub struct DataPair(pub f32, pub Data);
-impl DataPair {
-    pub fn to_data_triplet(self) -> DataTriplet {
-        DataTriplet{temp_part:0.0, thermal_data: self.1, temp_sub_part: self.0}
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-pub struct DataTriplet {
-    pub temp_part: f32, 
-    pub thermal_data: Data, 
-    pub temp_sub_part: f32
-}
-impl DataTriplet {
-    pub fn to_data_pair(self) -> DataPair {
-        DataPair(self.temp_part, self.thermal_data)
-    }
-}
+use super::ablator::{Ablator, AblatorParams};
+use super::info::Info;
+use super::interpolation::Interpolation;
+use super::property_table::PropertyTable;
+use super::steady::BackBoundary;
+use super::units::CostBasis;
+
+use std::{
+    ops::*,
+    path::PathBuf,
+};
+
+pub struct Part {
+    pub name: String,
+    pub description: String,
+    pub temp: f32,
+    /// portion weighted mean of its TPS
+    pub absorbation_const: f32,
+    /// the "CostPerArea" row, only used if none of its TPS has prices
+    pub cost_per_area: f32,
+    /// portion weighted costs of its TPS
+    pub cost_per_area_min: f32,
+    pub cost_per_area_max: f32,
+    pub has_ablator: bool,
+    pub height_min: f32,
+    pub height_max: f32,
+    pub areal_density_min: f32,
+    pub areal_density_max: f32,
+    pub tps_list: Vec<(TPS, f32, PropertyTable<LayerData>, PropertyTable<LayerData>)>,
+    pub data_min: PropertyTable<Data>,
+    pub data_max: PropertyTable<Data>,
+    /// `ModuleAblator` values of its ablating TPS, only with `has_ablator`
+    pub ablator: Option<AblatorParams>,
+    pub info: Option<Info>,
+}
+
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct TPS {
+    pub name: String,
+    pub description: String,
+    pub temp: f32,
+    /// solar absorptance of the top layer, `None` if its material has none
+    pub absorbation_const: Option<f32>,
+    pub data_min: PropertyTable<Data>,
+    pub areal_density_min: f32,
+    pub tickness_min: f32,
+    pub segments_min: Vec<Segment>,
+
+    pub data_max: PropertyTable<Data>,
+    pub areal_density_max: f32,
+    pub tickness_max: f32,
+    pub segments_max: Vec<Segment>,
+
+    /// `ModuleAblator` values, `None` without ablator layers
+    pub ablator: Option<AblatorParams>,
+    /// multiplies the material costs of the layers
+    pub manufacturing_factor: f32,
+    /// of the Min & Max section
+    pub cost_per_area_min: f32,
+    pub cost_per_area_max: f32,
+    pub info: Option<Info>,
+}
+impl Default for TPS{
+    fn default() -> Self {
+        TPS {
+            name: "".to_string(),
+            description: "".to_string(),
+            temp: 0.0,
+            absorbation_const: None,
+            data_min: PropertyTable::default(),
+            areal_density_min: 0.0,
+            tickness_min: 0.0,
+            segments_min: Vec::<Segment>::new(),        
+            data_max: PropertyTable::default(),
+            areal_density_max: 0.0,
+            tickness_max: 0.0,
+            segments_max: Vec::<Segment>::new(),       
+            ablator: None,
+            manufacturing_factor: 1.0,
+            cost_per_area_min: 0.0,
+            cost_per_area_max: 0.0,
+            info: None,
+        }
+    }
+}
+
+/// TPS as read from its csv, before any calculation
+#[derive(Debug, Clone, Default)]
+pub struct TpsSpec {
+    pub name: String,
+    pub description: String,
+    pub temp: f32,
+    pub segments_min: Vec<Segment>,
+    pub segments_max: Vec<Segment>,
+    /// back face of the steady profile that replaces the hot & cold sides of the file, see [`apply_steady_profile`](crate::apply_steady_profile)
+    pub back_boundary: Option<BackBoundary>,
+    /// "Manufacturing Factor" row, 1 without
+    pub manufacturing_factor: Option<f32>,
+    /// file the TPS was read from
+    pub path: PathBuf,
+    /// row keys the reader did not know, with their line
+    pub unknown_keys: Vec<(u64, String)>,
+    /// `.info` sidecar of the file
+    pub info: Option<Info>,
+}
+
+/// Part as read from its csv, before any calculation
+#[derive(Debug, Clone, Default)]
+pub struct PartSpec {
+    pub name: String,
+    pub description: String,
+    pub temp: f32,
+    /// stands in for the absorptance of TPS without one
+    pub absorbation_const: f32,
+    pub cost_per_area: f32,
+    pub has_ablator: bool,
+    pub structures: Vec<StructureRef>,
+    /// `.info` sidecar of the file
+    pub info: Option<Info>,
+}
+
+/// Reference from a part to one of its TPS.
+/// Heights of `f32::INFINITY` / `f32::NEG_INFINITY` stand for the TPS max / min thickness.
+#[derive(Debug, Clone)]
+pub struct StructureRef {
+    pub name: String,
+    pub portion: f32,
+    pub height_min: f32,
+    pub height_max: f32,
+}
+
+/// Temperature steps all curves get resampled onto
+#[derive(Debug, Clone)]
+pub struct TempGrid {
+    pub temps: Vec<f32>,
+    /// finer steps up to the last entry of `temps`, used for averaging across a layer
+    pub temps_fine: Vec<f32>,
+}
+impl TempGrid {
+    pub const FINE_STEP: usize = 5;
+
+    pub fn new(temps: Vec<f32>) -> Self {
+        let mut temps_fine = Vec::<f32>::new();
+        let last = temps.last().copied().unwrap_or(0.0);
+        for i in (0..=last as i32).step_by(Self::FINE_STEP) {
+            temps_fine.push(i as f32);
+        }
+        TempGrid { temps, temps_fine }
+    }
+
+    /// Grid from `start` to `stop` (inclusive) in steps of `step`
+    pub fn from_range(start: f32, stop: f32, step: f32) -> Self {
+        let mut temps = Vec::<f32>::new();
+        let mut i = 0;
+        while step > 0.0 && start + step * i as f32 <= stop {
+            temps.push(start + step * i as f32);
+            i += 1;
+        }
+        Self::new(temps)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub name: String,
+    pub path: String,
+    pub top_layer: bool,
+    pub portion: f32,
+    pub temp_max: f32,
+    pub temp_hot_side: f32,
+    pub temp_cold_side: f32,
+    pub density: f32,
+    pub tickness: f32,
+    pub areal_density: f32,
+    pub additive_areal_weight: f32,
+    pub data_csv: PropertyTable<Data>,
+    pub interpolation: Interpolation,
+    pub citation: Citation,
+    /// char & pyrolysis of ablative materials
+    pub ablator: Option<Ablator>,
+    /// price of the material, `None` without "Cost" row
+    pub cost: Option<Cost>,
+    /// solar absorptance α, the "Solar Absorptance" row
+    pub absorptance: Option<f32>,
+    pub data_tps_temp_map: PropertyTable<LayerData>,
+    pub data_height_adjust: PropertyTable<LayerData>,
+    pub data_avg_r: PropertyTable<LayerData>,
+    pub data_tps_temp_mult: PropertyTable<LayerData>,
+}
+impl Default for Segment{
+    fn default() -> Self {
+        Segment {
+            name: "".to_string(),
+            path: "".to_string(),
+            top_layer: false,
+            portion: 0.0,
+            temp_max: 0.0,
+            temp_hot_side: 0.0,
+            temp_cold_side: 0.0,
+            density: 0.0,
+            tickness: 0.0,
+            areal_density: 0.0,
+            additive_areal_weight: 0.0,
+            data_csv: PropertyTable::default(),
+            interpolation: Interpolation::default(),
+            citation: Citation::default(),
+            ablator: None,
+            cost: None,
+            absorptance: None,
+            data_height_adjust: PropertyTable::default(),
+            data_tps_temp_map: PropertyTable::default(),
+            data_tps_temp_mult: PropertyTable::default(),
+            data_avg_r: PropertyTable::default(),
+        }
+    }
+}
+
+/// Price of a material, the "Cost" row of its file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cost {
+    pub value: f32,
+    pub basis: CostBasis,
+}
+impl Cost {
+    /// Cost per m² of `segment`, its additive areal weight is not part of the material
+    pub fn per_area(&self, segment: &Segment) -> f32 {
+        match self.basis {
+            CostBasis::Mass          => self.value * segment.density * segment.tickness * segment.portion,
+            CostBasis::AreaThickness => self.value * segment.tickness * 1000.0 * segment.portion,
+        }
+    }
+}
+
+/// Where the data of a material comes from, the "Source", "Reference", "Notes" & "Confidence" rows of its file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Citation {
+    pub source: String,
+    /// one per "Reference" row
+    pub references: Vec<String>,
+    /// one per "Notes" row
+    pub notes: Vec<String>,
+    pub confidence: String,
+}
+impl Citation {
+    pub fn is_empty(&self) -> bool {
+        self.source.is_empty() && self.references.is_empty() && self.notes.is_empty() && self.confidence.is_empty()
+    }
+
+    /// `<material>: <source> (confidence: <confidence>)` followed by the indented references & notes
+    pub fn lines(&self, material: &str) -> Vec<String> {
+        let mut first = format!("{}:", material);
+        if !self.source.is_empty() {
+            first += &format!(" {}", self.source);
+        }
+        if !self.confidence.is_empty() {
+            first += &format!(" (confidence: {})", self.confidence);
+        }
+
+        let mut lines = vec![first];
+        lines.extend(self.references.iter().map(|reference| format!("    {}", reference)));
+        lines.extend(self.notes.iter().map(|note| format!("    Note: {}", note)));
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[allow(non_snake_case)]
+pub struct Data {
+    pub cp: f32,
+    pub R_th: f32,
+    pub e: f32
+}
+impl Add<Data> for Data {
+    type Output = Self;
+    fn add(self, other: Self) -> Self::Output {
+        Data{
+            cp: self.cp + other.cp,
+            R_th: self.R_th + other.R_th,
+            e: self.e + other.e,
+        }
+    }
+
+}
+impl Sub<Data> for Data {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self::Output {
+        Data{
+            cp: self.cp - other.cp,
+            R_th: self.R_th - other.R_th,
+            e: self.e - other.e,
+        }
+    }
+
+}
+impl Div<Data> for Data {
+    type Output = Self;
+    fn div(self, other: Self) -> Self::Output {
+        Data{
+            cp: self.cp / other.cp,
+            R_th: self.R_th / other.R_th,
+            e: self.e / other.e,
+        }
+    }
+
+}
+impl Div<f32> for Data {
+    type Output = Self;
+    fn div(self, other: f32) -> Self::Output {
+        Data{
+            cp: self.cp / other,
+            R_th: self.R_th / other,
+            e: self.e / other,
+        }
+    }
+
+}
+impl Mul<f32> for Data {
+    type Output = Self;
+    fn mul(self, other: f32) -> Self::Output {
+        Data{
+            cp: self.cp * other,
+            R_th: self.R_th * other,
+            e: self.e * other,
+        }
+    }
+
+}
+/// Values of a layer while its assembly is at a temperature, `temp_sub_part` is the layer's own temperature
+#[derive(Debug, Clone, Copy)]
+pub struct LayerData {
+    pub thermal_data: Data,
+    pub temp_sub_part: f32,
+}
+impl Add<LayerData> for LayerData {
+    type Output = Self;
+    fn add(self, other: Self) -> Self::Output {
+        LayerData {
+            thermal_data: self.thermal_data + other.thermal_data,
+            temp_sub_part: self.temp_sub_part + other.temp_sub_part,
+        }
+    }
+}
+impl Sub<LayerData> for LayerData {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self::Output {
+        LayerData {
+            thermal_data: self.thermal_data - other.thermal_data,
+            temp_sub_part: self.temp_sub_part - other.temp_sub_part,
+        }
+    }
+}
+impl Div<f32> for LayerData {
+    type Output = Self;
+    fn div(self, other: f32) -> Self::Output {
+        LayerData {
+            thermal_data: self.thermal_data / other,
+            temp_sub_part: self.temp_sub_part / other,
+        }
+    }
+}
+impl Mul<f32> for LayerData {
+    type Output = Self;
+    fn mul(self, other: f32) -> Self::Output {
+        LayerData {
+            thermal_data: self.thermal_data * other,
+            temp_sub_part: self.temp_sub_part * other,
+        }
+    }
+}
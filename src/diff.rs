@@ -0,0 +1,302 @@
+use super::data_holder::*;
+use super::error::CalcError;
+use super::preset::PresetData;
+use super::property_table::PropertyTable;
+use super::read_write::{get_files, read_curve_csv, read_preset_cfg};
+
+use std::{
+    env,
+    fmt,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Relative deviations a change may have before it counts as regression
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// for preset values
+    pub preset: f32,
+    /// for the rows of the csv curves
+    pub curve: f32,
+}
+
+/// Preset value that differs between two output trees
+#[derive(Debug, Clone)]
+pub struct ValueChange {
+    pub key: &'static str,
+    pub old: Option<f32>,
+    pub new: Option<f32>,
+}
+impl ValueChange {
+    /// `|new - old| / |old|`, infinite if the value was added, removed or isn't finite
+    pub fn relative(&self) -> f32 {
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => relative(old, new).abs(),
+            (None, None) => 0.0,
+            _ => f32::INFINITY,
+        }
+    }
+}
+impl fmt::Display for ValueChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = |value: Option<f32>| value.map_or("-".to_string(), |value| value.to_string());
+        match (self.old, self.new) {
+            (Some(old), Some(new)) => write!(f, "{} {} -> {} ({:+.2}%)", self.key, old, new, relative(old, new) * 100.0),
+            _ => write!(f, "{} {} -> {}", self.key, value(self.old), value(self.new)),
+        }
+    }
+}
+
+/// Largest deviation along a csv curve
+#[derive(Debug, Clone)]
+pub struct CurveChange {
+    pub file: String,
+    pub column: &'static str,
+    pub temp: f32,
+    pub old: f32,
+    pub new: f32,
+}
+impl CurveChange {
+    /// `|new - old| / |old|`, infinite if a value isn't finite
+    pub fn relative(&self) -> f32 {
+        relative(self.old, self.new).abs()
+    }
+}
+impl fmt::Display for CurveChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} at {} K {} -> {} ({:+.2}%)", self.file, self.column, self.temp, self.old, self.new, relative(self.old, self.new) * 100.0)
+    }
+}
+
+/// Changes of one preset, only those beyond the tolerance
+#[derive(Debug, Clone, Default)]
+pub struct PresetDiff {
+    pub name: String,
+    pub values: Vec<ValueChange>,
+    pub curves: Vec<CurveChange>,
+}
+
+/// Comparison of two output trees
+#[derive(Debug, Clone, Default)]
+pub struct OutputDiff {
+    pub changed: Vec<PresetDiff>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+impl OutputDiff {
+    /// Changed, added or removed presets. Added ones count too, a tree full of them means the wrong one was compared
+    pub fn is_regression(&self) -> bool {
+        !self.changed.is_empty() || !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for OutputDiff {
+    /// A line per added & removed preset and per change, then the counts
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for name in self.added.iter() {
+            writeln!(f, "Added {}", name)?;
+        }
+        for name in self.removed.iter() {
+            writeln!(f, "Removed {}", name)?;
+        }
+        for preset in self.changed.iter() {
+            for change in preset.values.iter() {
+                writeln!(f, "{}: {}", preset.name, change)?;
+            }
+            for change in preset.curves.iter() {
+                writeln!(f, "{}: {}", preset.name, change)?;
+            }
+        }
+        write!(f, "{} changed, {} added, {} removed", self.changed.len(), self.added.len(), self.removed.len())
+    }
+}
+
+/// Output tree the fresh one is compared with
+#[derive(Debug, Clone)]
+pub enum Baseline {
+    Directory(PathBuf),
+    /// `directory` as committed in the git revision `rev`
+    Revision { rev: String, directory: PathBuf },
+}
+
+/// Compare the output tree `new` with `baseline`, a revision gets checked out into `scratch` first
+pub fn diff_against(baseline: &Baseline, new: &Path, scratch: &Path, tolerance: Tolerance) -> Result<OutputDiff, CalcError> {
+    match baseline {
+        Baseline::Directory(old) => diff_outputs(old, new, tolerance),
+        Baseline::Revision { rev, directory } => {
+            checkout_revision(rev, directory, scratch)?;
+            diff_outputs(scratch, new, tolerance)
+        },
+    }
+}
+
+/// Compare the presets in TPS/ & Part/ of the output trees `old` & `new` together with their csv/ curves
+pub fn diff_outputs(old: &Path, new: &Path, tolerance: Tolerance) -> Result<OutputDiff, CalcError> {
+    let mut diff = OutputDiff::default();
+
+    for directory in ["TPS", "Part"] {
+        let old_presets = read_presets(&old.join(directory))?;
+        let new_presets = read_presets(&new.join(directory))?;
+
+        for new_preset in new_presets.iter() {
+            let Some(old_preset) = old_presets.iter().find(|preset| preset.name == new_preset.name) else {
+                diff.added.push(new_preset.name.clone());
+                continue;
+            };
+
+            let mut preset_diff = PresetDiff {
+                name: new_preset.name.clone(),
+                values: diff_preset(old_preset, new_preset),
+                ..Default::default()
+            };
+            preset_diff.values.retain(|change| change.relative() > tolerance.preset);
+
+            for suffix in ["_min.csv", "_max.csv"] {
+                let file = new_preset.name.clone() + suffix;
+                let old_file = old.join("csv").join(&file);
+                if !old_file.is_file() {
+                    continue;
+                }
+                let old_curve = read_curve_csv(&old_file)?;
+                let new_curve = read_curve_csv(&new.join("csv").join(&file))?;
+                if let Some(change) = diff_curve(&file, &old_curve, &new_curve) {
+                    if change.relative() > tolerance.curve {
+                        preset_diff.curves.push(change);
+                    }
+                }
+            }
+
+            if !preset_diff.values.is_empty() || !preset_diff.curves.is_empty() {
+                diff.changed.push(preset_diff);
+            }
+        }
+
+        diff.removed.extend(old_presets.iter()
+            .filter(|old_preset| !new_presets.iter().any(|preset| preset.name == old_preset.name))
+            .map(|old_preset| old_preset.name.clone()));
+    }
+    Ok(diff)
+}
+
+/// The preset values that differ at all
+pub fn diff_preset(old: &PresetData, new: &PresetData) -> Vec<ValueChange> {
+    let values = [
+        ("skinMassPerArea", old.areal_density_min, new.areal_density_min),
+        ("skinMassPerAreaMax", old.areal_density_max, new.areal_density_max),
+        ("skinSpecificHeatCapacity", old.cp_min, new.cp_min),
+        ("skinSpecificHeatCapacityMax", old.cp_max, new.cp_max),
+        ("thermalInsulance", old.insulance_min, new.insulance_min),
+        ("thermalInsulanceMax", old.insulance_max, new.insulance_max),
+        ("emissiveConstant", old.emissive_const, new.emissive_const),
+    ];
+    values.into_iter()
+        .filter(|(_key, old, new)| old != new || !old.is_none_or(f32::is_finite))
+        .map(|(key, old, new)| ValueChange { key, old, new })
+        .collect()
+}
+
+/// Largest relative deviation between rows of the same temperature, `None` if the curves share no row
+pub fn diff_curve(file: &str, old: &PropertyTable<Data>, new: &PropertyTable<Data>) -> Option<CurveChange> {
+    let mut largest: Option<CurveChange> = None;
+
+    for (temp, new_row) in new.iter() {
+        let Some((_temp, old_row)) = old.iter().find(|(old_temp, _row)| (old_temp - temp).abs() < 0.01) else { continue };
+        let columns = [
+            ("Heat Capacity", old_row.cp, new_row.cp),
+            ("Thermal Insulance", old_row.R_th, new_row.R_th),
+            ("Emissivity", old_row.e, new_row.e),
+        ];
+        for (column, old_value, new_value) in columns {
+            if largest.as_ref().is_none_or(|change| relative(old_value, new_value).abs() > change.relative()) {
+                largest = Some(CurveChange { file: file.to_string(), column, temp, old: old_value, new: new_value });
+            }
+        }
+    }
+    largest
+}
+
+/// Write the files below `directory` as committed in `rev` into `destination`
+pub fn checkout_revision(rev: &str, directory: &Path, destination: &Path) -> Result<(), CalcError> {
+    let git = |args: &[&str]| -> Result<Vec<u8>, CalcError> {
+        let output = Command::new("git").args(args).output()
+            .map_err(|err| CalcError::io(Path::new("git"), err))?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(CalcError::io(directory, io::Error::other(message)));
+        }
+        Ok(output.stdout)
+    };
+
+    // git lists paths relative to the top level, however `directory` was spelled
+    let absolute = canonicalize_missing(directory)?;
+    let repository = absolute.ancestors().find(|ancestor| ancestor.is_dir()).unwrap_or(&absolute).to_string_lossy().to_string();
+    let toplevel = String::from_utf8_lossy(&git(&["-C", &repository, "rev-parse", "--show-toplevel"])?).trim().to_string();
+    let toplevel = fs::canonicalize(&toplevel).map_err(|err| CalcError::io(Path::new(&toplevel), err))?;
+    let relative_dir = absolute.strip_prefix(&toplevel)
+        .map_err(|_err| CalcError::io(directory, io::Error::other(format!("not inside the repository {}", toplevel.display()))))?
+        .to_path_buf();
+    let toplevel = toplevel.to_string_lossy().to_string();
+
+    let listing = git(&["-C", &toplevel, "ls-tree", "-r", "-z", "--name-only", "--full-name", rev, "--", &relative_dir.to_string_lossy()])?;
+    let files = String::from_utf8_lossy(&listing).split_terminator('\0').map(PathBuf::from).collect::<Vec<PathBuf>>();
+    if files.is_empty() {
+        return Err(CalcError::io(directory, io::Error::other(format!("not found in revision {}", rev))));
+    }
+
+    for file in files.iter() {
+        let relative = file.strip_prefix(&relative_dir)
+            .map_err(|_err| CalcError::io(file, io::Error::other(format!("listed by git but not below {}", relative_dir.display()))))?;
+        let content = git(&["-C", &toplevel, "show", &format!("{}:{}", rev, file.display())])?;
+        let output_file = destination.join(relative);
+        if let Some(parent) = output_file.parent() {
+            fs::create_dir_all(parent).map_err(|err| CalcError::io(parent, err))?;
+        }
+        fs::write(&output_file, content).map_err(|err| CalcError::io(&output_file, err))?;
+    }
+    Ok(())
+}
+
+/// Absolute path of `path` with symlinks resolved, the part that doesn't exist (yet) is appended as is
+pub fn canonicalize_missing(path: &Path) -> Result<PathBuf, CalcError> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().map_err(|err| CalcError::io(path, err))?.join(path)
+    };
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else { break };
+        missing.push(name);
+        existing = parent;
+    }
+    let mut canonical = fs::canonicalize(existing).map_err(|err| CalcError::io(existing, err))?;
+    canonical.extend(missing.into_iter().rev());
+    Ok(canonical)
+}
+
+fn read_presets(directory: &Path) -> Result<Vec<PresetData>, CalcError> {
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut presets = Vec::<PresetData>::new();
+    for path in get_files(directory, "cfg")? {
+        presets.append(&mut read_preset_cfg(&path)?);
+    }
+    Ok(presets)
+}
+
+/// Signed change relative to `old`, infinite if either isn't finite so that NaN & inf never pass as within tolerance
+fn relative(old: f32, new: f32) -> f32 {
+    if !old.is_finite() || !new.is_finite() {
+        f32::INFINITY
+    } else if old == new {
+        0.0
+    } else if old == 0.0 {
+        f32::INFINITY.copysign(new)
+    } else {
+        (new - old) / old.abs()
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use super::validate::Issue;
+
+#[derive(Debug, Error)]
+pub enum CalcError {
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    #[error("{}: {source}", path.display())]
+    Csv { path: PathBuf, source: csv::Error },
+
+    #[error("{}:{line}: can not parse {column} \"{value}\"", path.display())]
+    Parse { path: PathBuf, line: u64, column: String, value: String },
+
+    #[error("{}:{line}: {message}", path.display())]
+    Config { path: PathBuf, line: u64, message: String },
+
+    #[error("{}: lacks \"{key}\" entry", path.display())]
+    Missing { path: PathBuf, key: String },
+
+    #[error("{}: material \"{reference}\" not found{}", path.display(),
+            suggestion.as_ref().map_or(String::new(), |name| format!(", did you mean \"{}\"?", name)))]
+    MaterialNotFound { path: PathBuf, reference: String, suggestion: Option<String> },
+
+    #[error("{}: material \"{reference}\" is ambiguous, it is the name of {}, refer to one by path", path.display(),
+            files.iter().map(|file| file.display().to_string()).collect::<Vec<String>>().join(" & "))]
+    AmbiguousMaterial { path: PathBuf, reference: String, files: Vec<PathBuf> },
+
+    #[error("{0}")]
+    Invalid(Issue),
+
+    #[error("table has no rows")]
+    EmptyTable,
+
+    #[error("temperature {temp} K is outside of the table ({min} K to {max} K)")]
+    OutOfRange { temp: f32, min: f32, max: f32 },
+
+    #[error("simulation: {0}")]
+    Simulation(String),
+
+    #[error("optimization: {0}")]
+    Optimization(String),
+
+    #[error("{kind} not found. {names:?}")]
+    NameNotFound { kind: &'static str, names: Vec<String> },
+
+    #[error("Part {part}: Structures not found. {names:?}")]
+    StructureNotFound { part: String, names: Vec<String> },
+
+    #[error("Part {part}: HasAblator is set, but none of its structures has an ablator layer")]
+    NoAblator { part: String },
+}
+
+impl CalcError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        CalcError::Io { path: path.to_path_buf(), source }
+    }
+
+    pub fn csv(path: &Path, source: csv::Error) -> Self {
+        CalcError::Csv { path: path.to_path_buf(), source }
+    }
+
+    pub fn parse(path: &Path, line: u64, column: &str, value: &str) -> Self {
+        CalcError::Parse { path: path.to_path_buf(), line, column: column.to_string(), value: value.to_string() }
+    }
+
+    pub fn config(path: &Path, line: u64, message: &str) -> Self {
+        CalcError::Config { path: path.to_path_buf(), line, message: message.to_string() }
+    }
+
+    pub fn missing(path: &Path, key: &str) -> Self {
+        CalcError::Missing { path: path.to_path_buf(), key: key.to_string() }
+    }
+}
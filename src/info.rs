@@ -0,0 +1,104 @@
+//! `.info` sidecars of TPS & part files: who modeled them after which vehicle, and from which sources.
+//!
+//! One `Key: value` entry per line, `#` starts a comment line. Every key but `Author` & `Vehicle` may repeat.
+//! The sidecar `<stem>.info` belongs to `<stem>.csv` next to it, `Applies To` names further files of the folder.
+//!
+//! ```
+//! use std::path::Path;
+//! use ro_materials_csv_calculator::Info;
+//!
+//! let text = "# X-15 hot structure\nVehicle: North American X-15\nApplies To: X-15 Wing\nReference: X-15 design report";
+//! let info = Info::parse(Path::new("X-15.info"), text).unwrap();
+//! assert_eq!(info.vehicle, "North American X-15");
+//! assert!(info.applies_to(Path::new("part/X-15 Wing.csv")));
+//! ```
+
+use super::data_holder::{Part, TPS};
+use super::error::CalcError;
+
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Info {
+    /// file the sidecar was read from
+    pub path: PathBuf,
+    /// file stems besides its own the sidecar belongs to
+    pub applies_to: Vec<String>,
+    pub author: String,
+    /// vehicle the TPS or part was modeled after
+    pub vehicle: String,
+    pub references: Vec<String>,
+    /// how the results were checked, e.g. against flight data
+    pub validation: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl Info {
+    /// Parse the text of a sidecar, `path` is kept & used for errors
+    pub fn parse(path: &Path, text: &str) -> Result<Info, CalcError> {
+        let mut info = Info { path: path.to_path_buf(), ..Default::default() };
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i as u64 + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(CalcError::config(path, line_number, "expected \"Key: value\""));
+            };
+            let value = value.trim().to_string();
+            match key.trim() {
+                "Applies To" => info.applies_to.push(value),
+                "Author"     => info.author = value,
+                "Vehicle"    => info.vehicle = value,
+                "Reference"  => info.references.push(value),
+                "Validation" => info.validation.push(value),
+                "Notes"      => info.notes.push(value),
+                key          => return Err(CalcError::config(path, line_number, &format!("unknown key \"{}\"", key))),
+            }
+        }
+        Ok(info)
+    }
+
+    /// Whether the sidecar belongs to the TPS or part file `csv_path`
+    pub fn applies_to(&self, csv_path: &Path) -> bool {
+        let Some(stem) = csv_path.file_stem().and_then(|stem| stem.to_str()) else { return false };
+        self.path.file_stem().and_then(|own| own.to_str()) == Some(stem) || self.applies_to.iter().any(|name| name == stem)
+    }
+
+    /// Lines for the comment block of a preset
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::<String>::new();
+        if !self.vehicle.is_empty() {
+            lines.push(format!("Vehicle: {}", self.vehicle));
+        }
+        if !self.author.is_empty() {
+            lines.push(format!("Author: {}", self.author));
+        }
+        lines.extend(self.references.iter().map(|reference| format!("Reference: {}", reference)));
+        lines.extend(self.validation.iter().map(|validation| format!("Validation: {}", validation)));
+        lines.extend(self.notes.iter().map(|note| format!("Note: {}", note)));
+        lines
+    }
+}
+
+/// Row of the index document
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    /// "TPS" or "Part"
+    pub kind: &'static str,
+    pub name: String,
+    pub description: String,
+    pub info: Option<Info>,
+}
+
+impl IndexEntry {
+    pub fn from_tps(tps: &TPS) -> Self {
+        IndexEntry { kind: "TPS", name: tps.name.clone(), description: tps.description.clone(), info: tps.info.clone() }
+    }
+
+    pub fn from_part(part: &Part) -> Self {
+        IndexEntry { kind: "Part", name: part.name.clone(), description: part.description.clone(), info: part.info.clone() }
+    }
+}
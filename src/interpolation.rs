@@ -0,0 +1,192 @@
+//! Interpolation over temperature tables: linear, monotone cubic & log-linear.
+//!
+//! Sample temperatures are expected in ascending order, as material & TPS tables are written.
+
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Values that can be interpolated linearly, e.g. `f32` & [`Data`](crate::Data)
+pub trait Lerp: Copy + Add<Output = Self> + Sub<Output = Self> + Div<f32, Output = Self> + Mul<f32, Output = Self> {}
+impl<T> Lerp for T where T: Copy + Add<Output = T> + Sub<Output = T> + Div<f32, Output = T> + Mul<f32, Output = T> {}
+
+/// Where a temperature lies relative to the sample temperatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    /// before the first sample, or no samples at all
+    Below,
+    /// exactly on sample `i`
+    At(usize),
+    /// between sample `i` & `i + 1`
+    Between(usize),
+    /// after the last sample
+    Above,
+}
+
+/// Position of `x` in the ascending `xs`
+pub fn locate(xs: &[f32], x: f32) -> Position {
+    // first sample not below x
+    let i = xs.partition_point(|sample| *sample < x);
+    if i == xs.len() {
+        if xs.is_empty() { Position::Below } else { Position::Above }
+    } else if xs[i] == x {
+        Position::At(i)
+    } else if i == 0 {
+        Position::Below
+    } else {
+        Position::Between(i - 1)
+    }
+}
+
+/// Value at `x` on the line through (`x0`, `y0`) & (`x1`, `y1`), also outside of them
+pub fn lerp<T: Lerp>(x0: f32, y0: T, x1: f32, y1: T, x: f32) -> T {
+    (y1 - y0) / (x1 - x0) * (x - x0) + y0
+}
+
+/// Value at `x` in the table `xs`/`ys`, held constant outside of it. `None` for an empty table
+pub fn interpolate<T: Lerp>(xs: &[f32], ys: &[T], x: f32) -> Option<T> {
+    match locate(xs, x) {
+        Position::Below      => ys.first().copied(),
+        Position::At(i)      => Some(ys[i]),
+        Position::Between(i) => Some(lerp(xs[i], ys[i], xs[i + 1], ys[i + 1], x)),
+        Position::Above      => ys.last().copied(),
+    }
+}
+
+/// Fill the gaps (zeros) of a table column: linear between the known neighbours,
+/// the first & last known value are held towards the ends. A column without any value stays empty.
+pub fn fill_gaps(xs: &[f32], ys: &mut [f32]) {
+    fill_gaps_with(Scheme::Linear, xs, ys);
+}
+
+/// [`fill_gaps`] with `scheme` between the known values
+pub fn fill_gaps_with(scheme: Scheme, xs: &[f32], ys: &mut [f32]) {
+    let known = (0..ys.len()).filter(|i| ys[*i] != 0.0).collect::<Vec<usize>>();
+    let (Some(&first), Some(&last)) = (known.first(), known.last()) else { return };
+
+    let value = ys[first];
+    ys[..first].fill(value);
+    let value = ys[last];
+    ys[last + 1..].fill(value);
+
+    let xs_known = known.iter().map(|i| xs[*i]).collect::<Vec<f32>>();
+    let ys_known = known.iter().map(|i| ys[*i]).collect::<Vec<f32>>();
+    let slopes = scheme.slopes(&xs_known, &ys_known);
+    for (k, pair) in known.windows(2).enumerate() {
+        for i in pair[0] + 1..pair[1] {
+            ys[i] = scheme.segment(&xs_known, &ys_known, &slopes, k, xs[i]);
+        }
+    }
+}
+
+/// How a column is interpolated between its rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scheme {
+    #[default]
+    Linear,
+    /// piecewise cubic Hermite (PCHIP), no overshoot between monotone rows
+    MonotoneCubic,
+    /// linear in ln(y), for values growing exponentially. Segments touching values <= 0 stay linear
+    LogLinear,
+}
+
+impl Scheme {
+    /// Name as written in material files, also accepts "pchip" & "log"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "linear"                   => Some(Scheme::Linear),
+            "monotone-cubic" | "pchip" => Some(Scheme::MonotoneCubic),
+            "log-linear" | "log"       => Some(Scheme::LogLinear),
+            _                          => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scheme::Linear        => "linear",
+            Scheme::MonotoneCubic => "monotone-cubic",
+            Scheme::LogLinear     => "log-linear",
+        }
+    }
+
+    /// Slopes at the rows the cubic scheme needs, empty for the others
+    pub fn slopes(&self, xs: &[f32], ys: &[f32]) -> Vec<f32> {
+        match self {
+            Scheme::MonotoneCubic => pchip_slopes(xs, ys),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Value at `x` between row `i` & `i + 1`, `slopes` from [`Scheme::slopes`]
+    pub fn segment(&self, xs: &[f32], ys: &[f32], slopes: &[f32], i: usize, x: f32) -> f32 {
+        let (x0, x1, y0, y1) = (xs[i], xs[i + 1], ys[i], ys[i + 1]);
+        match self {
+            Scheme::MonotoneCubic if slopes.len() == xs.len() => {
+                let h = x1 - x0;
+                let t = (x - x0) / h;
+                let (t2, t3) = (t * t, t * t * t);
+                (2.0 * t3 - 3.0 * t2 + 1.0) * y0 + (t3 - 2.0 * t2 + t) * h * slopes[i]
+                    + (-2.0 * t3 + 3.0 * t2) * y1 + (t3 - t2) * h * slopes[i + 1]
+            },
+            Scheme::LogLinear if y0 > 0.0 && y1 > 0.0 => lerp(x0, y0.ln(), x1, y1.ln(), x).exp(),
+            _ => lerp(x0, y0, x1, y1, x),
+        }
+    }
+
+    /// Value at `x` in the table `xs`/`ys`, held constant outside of it. `None` for an empty table
+    pub fn interpolate(&self, xs: &[f32], ys: &[f32], x: f32) -> Option<f32> {
+        match locate(xs, x) {
+            Position::Between(i) => Some(self.segment(xs, ys, &self.slopes(xs, ys), i, x)),
+            _ => interpolate(xs, ys, x),
+        }
+    }
+}
+
+/// Scheme of each material column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interpolation {
+    pub specific_heat: Scheme,
+    pub conductivity: Scheme,
+    pub emissivity: Scheme,
+}
+
+impl Interpolation {
+    pub fn all(scheme: Scheme) -> Self {
+        Interpolation { specific_heat: scheme, conductivity: scheme, emissivity: scheme }
+    }
+}
+
+/// Fritsch-Carlson slopes as used by PCHIP, one per row
+pub fn pchip_slopes(xs: &[f32], ys: &[f32]) -> Vec<f32> {
+    let n = xs.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+    let h = xs.windows(2).map(|pair| pair[1] - pair[0]).collect::<Vec<f32>>();
+    let delta = (0..n - 1).map(|k| (ys[k + 1] - ys[k]) / h[k]).collect::<Vec<f32>>();
+    if n == 2 {
+        return vec![delta[0]; 2];
+    }
+
+    let mut slopes = vec![0.0; n];
+    for k in 1..n - 1 {
+        if delta[k - 1] * delta[k] > 0.0 {
+            let w1 = 2.0 * h[k] + h[k - 1];
+            let w2 = h[k] + 2.0 * h[k - 1];
+            slopes[k] = (w1 + w2) / (w1 / delta[k - 1] + w2 / delta[k]);
+        }
+    }
+    slopes[0] = pchip_end_slope(h[0], h[1], delta[0], delta[1]);
+    slopes[n - 1] = pchip_end_slope(h[n - 2], h[n - 3], delta[n - 2], delta[n - 3]);
+    slopes
+}
+
+/// Three point estimate at an end, limited so the end segment stays monotone
+fn pchip_end_slope(h0: f32, h1: f32, delta0: f32, delta1: f32) -> f32 {
+    let slope = ((2.0 * h0 + h1) * delta0 - h0 * delta1) / (h0 + h1);
+    if slope.signum() != delta0.signum() || delta0 == 0.0 {
+        0.0
+    } else if delta0.signum() != delta1.signum() && slope.abs() > 3.0 * delta0.abs() {
+        3.0 * delta0
+    } else {
+        slope
+    }
+}
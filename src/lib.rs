@@ -0,0 +1,152 @@
+//! Calculates temperature dependent thermal properties of TPS (thermal protection system) stacks
+//! and the parts built from them, and writes them as `ROThermal_PRESET` configs for Kerbal Space Program.
+//!
+//! The pipeline has two stages:
+//! - [`compute_tps`] turns a [`TpsSpec`] (layers with their material data) into a [`TPS`]
+//! - [`compute_part`] combines already computed [`TPS`] into a [`Part`]
+//!
+//! ```no_run
+//! use std::path::PathBuf;
+//! use ro_materials_csv_calculator::*;
+//!
+//! let temp_grid = TempGrid::new(read_temp_list_csv2(&PathBuf::from("bib/Temp_List.csv")).unwrap());
+//! let library = MaterialLibrary::new(&PathBuf::from("bib"));
+//! let spec = load_tps_spec(&PathBuf::from("bib/tps/HRSI_LI-900.csv"), &library).unwrap();
+//! let tps = compute_tps(&spec, &temp_grid);
+//! println!("{} kg/m² at {} m", tps.areal_density_min, tps.tickness_min);
+//! ```
+
+pub mod ablator;
+pub mod calculation;
+pub mod config_node;
+pub mod data_holder;
+pub mod diff;
+pub mod error;
+pub mod info;
+pub mod interpolation;
+pub mod material_library;
+pub mod optimize;
+pub mod preset;
+pub mod property_table;
+pub mod read_write;
+pub mod steady;
+pub mod transient;
+pub mod units;
+pub mod validate;
+
+pub use ablator::{Ablator, AblatorParams};
+pub use calculation::*;
+pub use config_node::ConfigNode;
+pub use data_holder::*;
+pub use diff::{checkout_revision, diff_against, diff_outputs, Baseline, OutputDiff, Tolerance};
+pub use error::CalcError;
+pub use info::{IndexEntry, Info};
+pub use interpolation::{Interpolation, Scheme};
+pub use material_library::MaterialLibrary;
+pub use optimize::{optimize, stack_areal_density, LoadCase, OptimizeSettings, Optimum, ThicknessBounds};
+pub use preset::{HeatPulse, PatchOp, Preset, PresetData, PresetFormat, ReferenceTemp};
+pub use property_table::{Extrapolation, PropertyTable};
+pub use read_write::*;
+pub use steady::{apply_steady_profile, steady_profile, BackBoundary, SteadyProfile};
+pub use transient::{compare, simulate, simulate_skin, BackFace, ModelError, SkinModel, Surface, Transient, TransientPoint, TransientSettings};
+pub use units::{CostBasis, TemperatureUnit, Units};
+pub use validate::{check_margins, temperature_margins, validate_tps, Issue, LayerMargin, Severity};
+
+/// Calculate the thermal properties of a TPS from its layers.
+///
+/// The segments of `spec` need their material data loaded, see [`load_tps_spec`].
+/// Check it with [`validate_tps`] first, specs with errors can panic here. Without Max layers the Min ones are used for both.
+pub fn compute_tps(spec: &TpsSpec, temp_grid: &TempGrid) -> TPS {
+    let segments_max = if spec.segments_max.is_empty() { &spec.segments_min } else { &spec.segments_max };
+    let mut tps = TPS {
+        name: spec.name.clone(),
+        description: spec.description.clone(),
+        temp: spec.temp,
+        segments_min: spec.segments_min.clone(),
+        segments_max: segments_max.clone(),
+        absorbation_const: spec.segments_min.iter().chain(spec.segments_max.iter())
+            .find(|segment| segment.top_layer)
+            .and_then(|segment| segment.absorptance),
+        manufacturing_factor: spec.manufacturing_factor.unwrap_or(1.0),
+        info: spec.info.clone(),
+        ..Default::default()
+    };
+
+    for segment in tps.segments_min.iter_mut() {
+        calc_segment(segment, tps.temp, temp_grid);
+    }
+    for segment in tps.segments_max.iter_mut() {
+        calc_segment(segment, tps.temp, temp_grid);
+    }
+    calc_tps_height_density(&mut tps);
+    tps.ablator = AblatorParams::from_tps(&tps);
+
+    for segment in tps.segments_min.iter_mut() {
+        segment.data_tps_temp_mult = tps_value_mult(tps.areal_density_min, segment.areal_density, &segment.data_avg_r);
+    }
+    for segment in tps.segments_max.iter_mut() {
+        segment.data_tps_temp_mult = tps_value_mult(tps.areal_density_max, segment.areal_density, &segment.data_avg_r);
+    }
+    tps.data_min = calc_tps_data(&tps.segments_min, &temp_grid.temps);
+    tps.data_max = calc_tps_data(&tps.segments_max, &temp_grid.temps);
+    tps
+}
+
+/// Calculate the thermal properties of a part from the TPS it is made of.
+///
+/// Every structure referenced by `spec` has to be present in `tps_list`.
+pub fn compute_part(spec: &PartSpec, tps_list: &[TPS], temp_grid: &TempGrid) -> Result<Part, CalcError> {
+    let mut part = Part {
+        name: spec.name.clone(),
+        description: spec.description.clone(),
+        temp: spec.temp,
+        absorbation_const: spec.absorbation_const,
+        cost_per_area: spec.cost_per_area,
+        cost_per_area_min: 0.0,
+        cost_per_area_max: 0.0,
+        has_ablator: spec.has_ablator,
+        height_min: 0.0,
+        height_max: 0.0,
+        areal_density_min: 0.0,
+        areal_density_max: 0.0,
+        tps_list: Vec::<(TPS, f32, PropertyTable<LayerData>, PropertyTable<LayerData>)>::new(),
+        data_min: PropertyTable::default(),
+        data_max: PropertyTable::default(),
+        ablator: None,
+        info: spec.info.clone(),
+    };
+    let mut structures = spec.structures.clone();
+
+    for tps in tps_list {
+        if let Some(i) = structures.iter().position(|structure| structure.name == tps.name) {
+            let structure = structures.remove(i);
+            let mut tps_new = tps_change_height(tps, structure.height_min, structure.height_max);
+            tps_new.absorbation_const.get_or_insert(spec.absorbation_const);
+            let data_min = map_component_data_to_assembly(part.temp, tps.temp, &tps_new.data_min, Interpolation::default(), &temp_grid.temps);
+            let data_max = map_component_data_to_assembly(part.temp, tps.temp, &tps_new.data_max, Interpolation::default(), &temp_grid.temps);
+
+            part.tps_list.push((tps_new, structure.portion, data_min, data_max));
+        }
+
+        if structures.is_empty() {
+            break;
+        }
+    }
+    if !structures.is_empty() {
+        let names = structures.into_iter().map(|structure| structure.name).collect::<Vec<String>>();
+        return Err(CalcError::StructureNotFound { part: part.name, names });
+    }
+
+    if part.has_ablator {
+        let ablators = part.tps_list.iter()
+            .filter_map(|(tps, portion, _, _)| tps.ablator.map(|ablator| (ablator, *portion)))
+            .collect::<Vec<(AblatorParams, f32)>>();
+        part.ablator = AblatorParams::from_parts(&ablators);
+        if part.ablator.is_none() {
+            return Err(CalcError::NoAblator { part: part.name });
+        }
+    }
+
+    calculate_part(&mut part, &temp_grid.temps)?;
+    Ok(part)
+}
@@ -1,505 +1,715 @@
-mod read_write;
-mod data_holder;
-
-use read_write::*;
-use data_holder::*;
+use ro_materials_csv_calculator::*;
 
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum, error::ErrorKind};
 use std::{
+    env,
+    fs,
     process,
-    path::PathBuf, 
-    ffi::OsString,
+    path::{Path, PathBuf},
 };
 
 
-const TEMPERATURE_EQUALIZED: f32 = 0.0;//273.15;
-const TEMP_STEP: f32 = 50.0;
-const TEMP_LIST: &str = "bib/Temp_List.csv";
-const OUTPUT_DIRECTORY: &str = "out/";
+#[derive(Parser, Clone)]
+#[command(version, about = "Calculates ROThermal presets from material, TPS & part csv files")]
+struct Cli {
+    /// Library root holding materials/, tps/, part/ & Temp_List.csv
+    #[arg(short, long, global = true, default_value = "bib")]
+    library: PathBuf,
 
+    /// Output directory
+    #[arg(short, long, global = true, default_value = "out")]
+    out: PathBuf,
 
-fn main() -> std::io::Result<()> {
-    let mut tps_list = Vec::<TPS>::new();
-    let temp_list = read_temp_list_csv2(&PathBuf::from(TEMP_LIST));
+    /// Temperature list csv [default: <LIBRARY>/Temp_List.csv]
+    #[arg(long, global = true, value_name = "FILE")]
+    temp_list: Option<PathBuf>,
 
-    let mut temp_list_5 = Vec::<f32>::new();
-    for i in (0..=*temp_list.last().unwrap() as i32).step_by(5) {
-        temp_list_5.push(i as f32);
-    }
+    /// Generate the temperature list instead of reading it
+    #[arg(long, global = true, num_args = 3, value_names = ["START", "STOP", "STEP"], conflicts_with = "temp_list")]
+    temp_range: Option<Vec<f32>>,
 
-    let tps_paths = get_files("bib/tps".to_string(), OsString::from("csv"));
+    /// Treat warnings, e.g. layers above their Temperature Limit, as errors
+    #[arg(long, global = true)]
+    strict: bool,
 
-    for path in tps_paths.iter() {
-        let mut tps = read_tps_csv(path);
+    /// Write presets as ModuleManager patches instead of bare nodes
+    #[arg(long, global = true, value_enum)]
+    patch: Option<Patch>,
 
-        for segment in tps.segments_min.iter_mut() {
-            read_material_csv(segment).unwrap();
-            fill_gaps_in_csv(&mut segment.data_csv);
-            segment.areal_density = (segment.density * segment.tickness + segment.additive_areal_weight) * segment.portion ;
-            
-            //println!("{}, {}", tps.name, segment.name);
-            segment.data_tps_temp_map = map_component_data_to_assembly(tps.temp, segment.temp_hot_side, &segment.data_csv, &temp_list);
-            segment.data_height_adjust = adjust_to_height(segment.tickness * segment.portion, &segment.data_tps_temp_map);
-            segment.data_avg_r = avg_cp_k(segment.tickness, &segment.data_height_adjust, segment.temp_hot_side, segment.temp_cold_side, &temp_list_5);
-        }
-        for segment in tps.segments_max.iter_mut() {
-            read_material_csv(segment).unwrap();
-            fill_gaps_in_csv(&mut segment.data_csv);
-            segment.areal_density = (segment.density * segment.tickness + segment.additive_areal_weight) * segment.portion ;
-            
-            //println!("{}, {}", tps.name, segment.name);
-            segment.data_tps_temp_map = map_component_data_to_assembly(tps.temp, segment.temp_hot_side, &segment.data_csv, &temp_list);
-            segment.data_height_adjust = adjust_to_height(segment.tickness * segment.portion, &segment.data_tps_temp_map);
-            segment.data_avg_r = avg_cp_k(segment.tickness, &segment.data_height_adjust, segment.temp_hot_side, segment.temp_cold_side, &temp_list_5);
-        }
-        calc_tps_height_density(&mut tps);
-        for segment in tps.segments_min.iter_mut() {
-            segment.data_tps_temp_mult = tps_value_mult(tps.areal_density_min,segment.areal_density ,&segment.data_avg_r);
-        }
-        for segment in tps.segments_max.iter_mut() {
-            segment.data_tps_temp_mult = tps_value_mult(tps.areal_density_max,segment.areal_density ,&segment.data_avg_r);
-        }
-        tps.data_min = calc_tps_data(&tps.segments_min, &temp_list);
-        tps.data_max = calc_tps_data(&tps.segments_max, &temp_list);
-        
+    /// Mods for the :NEEDS[...] clause of patches, can be repeated
+    #[arg(long, global = true, value_name = "MOD", value_delimiter = ',')]
+    needs: Vec<String>,
 
-        output_tps(&tps, OUTPUT_DIRECTORY.to_string()).unwrap();
-        tps_list.push(tps);
-    }
-    
+    /// Mod for the :FOR[...] clause of patches
+    #[arg(long = "for", global = true, value_name = "MOD")]
+    for_mod: Option<String>,
 
-    let part_paths = get_files("bib/part".to_string(), OsString::from("csv"));
-    for path in part_paths.iter() {
-        let (mut part, mut part_list_min) = read_part_csv(path);
+    /// Write emissivity, heat capacity & insulance as FloatCurve nodes instead of their single value
+    #[arg(long, global = true)]
+    curves: bool,
 
-        for tps in &tps_list {
-            for (i, (name, portion, height_min, height_max)) in part_list_min.iter_mut().enumerate() {
-                if tps.name == *name {
-                    let tps_new = tps_change_height(&tps,*height_min, *height_max );
-                    let data_min = map_component_data_to_assembly(part.temp, tps.temp, &tps_new.data_min, &temp_list);
-                    let data_max = map_component_data_to_assembly(part.temp, tps.temp, &tps_new.data_max, &temp_list);
+    /// Temperature the single preset values are taken at: max, a fraction of max like 0.8, or pulse:<CSV> for their mean
+    /// over the surface temperature history in the csv, weighted by T^4 & time
+    #[arg(long, global = true, value_name = "REFERENCE", default_value = "max", value_parser = parse_reference)]
+    reference: ReferenceTemp,
 
-                    part.tps_list.push((tps_new, *portion, data_min, data_max));
-                    part_list_min.remove(i);
-                    break;
-                }
-            }
+    /// Also write all presets sorted by name into this file inside the output directory
+    #[arg(long, global = true, value_name = "FILE")]
+    combined: Option<PathBuf>,
 
-            if part_list_min.is_empty() {
-                break;
-            }
-        }
-        if !part_list_min.is_empty() {
-            println!("Error Part {}: Structures not found. {:?}", part.name, part_list_min);
-            process::exit(1);
-        }
+    #[command(subcommand)]
+    command: Option<Command>,
+}
 
-    
-        calculate_part(&mut part, &temp_list);
+#[derive(Clone, Copy, ValueEnum)]
+enum Patch {
+    /// ROThermal_PRESET
+    Create,
+    /// @ROThermal_PRESET[name], edits an existing preset
+    Edit,
+    /// %ROThermal_PRESET[name], edits or creates the preset
+    Replace,
+}
 
-        output_part(part, OUTPUT_DIRECTORY.to_string()).unwrap();
+impl Cli {
+    fn preset_format(&self) -> PresetFormat {
+        let patch = self.patch.map(|patch| match patch {
+            Patch::Create  => PatchOp::Create,
+            Patch::Edit    => PatchOp::Edit,
+            Patch::Replace => PatchOp::Replace,
+        });
+        PresetFormat { patch, needs: self.needs.clone(), for_mod: self.for_mod.clone(), curves: self.curves, reference: self.reference.clone() }
     }
 
-    Ok(())
-}
-
-fn fill_gaps_in_csv(thermal_list: &mut Vec<DataPair>,) {
-    let mut lower_bound = usize::MAX;
-    let mut upper_bound = usize::MAX;
-    
-    let mut n_cp = usize::MAX;
-    let mut n_r_th = usize::MAX;
-    let mut n_e = usize::MAX;
-
-
-    // copy first non zero entry to all entries before
-    for i in 0..thermal_list.len() {
-        if thermal_list[i].1.cp != 0.0 && n_cp == usize::MAX {
-            n_cp = i;
-            for j in 0..i {
-                thermal_list[j].1.cp = thermal_list[i].1.cp 
-            }
-        }
-        if thermal_list[i].1.R_th != 0.0 && n_r_th == usize::MAX {
-            n_r_th = i;
-            for j in 0..i {
-                thermal_list[j].1.R_th = thermal_list[i].1.R_th 
-            }
-        }
-        if thermal_list[i].1.e != 0.0 && n_e == usize::MAX {
-            n_e = i;
-            for j in 0..i {
-                thermal_list[j].1.e = thermal_list[i].1.e 
-            }
-        }
-        if n_cp != usize::MAX && n_r_th != usize::MAX && n_e != usize::MAX {
-            break;
+    /// Write the combined preset file if asked for
+    fn output_combined(&self, mut presets: Vec<Preset>, report: &mut Report) {
+        let Some(file) = &self.combined else { return };
+        if let Err(err) = output_presets(&mut presets, &self.out.join(file), &self.preset_format()) {
+            report.errors.push(err);
         }
     }
+}
 
-    // Fill the gaps inbetween
-    for i in n_cp..thermal_list.len() {
-        if thermal_list[i].1.cp == 0.0 && lower_bound == usize::MAX && i > 0{
-            lower_bound = i-1;
-            n_cp = lower_bound;
-        }
-        if thermal_list[i].1.cp != 0.0 && lower_bound != usize::MAX {
-            upper_bound = i;
-        }
-        if upper_bound != usize::MAX && lower_bound != usize::MAX {
-            let temp_delta = thermal_list[upper_bound].0 - thermal_list[lower_bound].0;
-            let data_delta = thermal_list[upper_bound].1.cp - thermal_list[lower_bound].1.cp;
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Build all TPS & parts of the library (default)
+    Build(Filter),
+    /// Build a single TPS file
+    Tps { file: PathBuf },
+    /// Build a single part file, its TPS are taken from the library
+    Part { file: PathBuf },
+    /// Write the gap filled table of a material file
+    Material { file: PathBuf },
+    /// Print the ROThermal_PRESET nodes of a cfg file
+    Read { file: PathBuf },
+    /// Rebuild the library & compare the presets with the output directory or a git revision of it
+    Diff(DiffArgs),
+    /// Run a heat flux or surface temperature history through the layers of a TPS file
+    Simulate(SimulateArgs),
+    /// Find the lightest layer thicknesses of a TPS file that keep back face & layers below their limits
+    Optimize(OptimizeArgs),
+}
 
-            for j in (lower_bound + 1)..upper_bound {
-                thermal_list[j].1.cp = data_delta / temp_delta * (thermal_list[j].0 - thermal_list[lower_bound].0) + thermal_list[lower_bound].1.cp;
-            }
-            upper_bound = usize::MAX;
-            lower_bound = usize::MAX;
-        }
-    }
+#[derive(Args, Clone)]
+struct DiffArgs {
+    /// Compare with this directory instead of the output directory
+    #[arg(long, value_name = "DIR")]
+    against: Option<PathBuf>,
 
-    upper_bound = usize::MAX;
-    lower_bound = usize::MAX;
-    for i in n_r_th..thermal_list.len() {
-        if thermal_list[i].1.R_th == 0.0 && lower_bound == usize::MAX && i > 0{
-            lower_bound = i-1;
-            n_r_th = lower_bound;
-        }
-        if thermal_list[i].1.R_th != 0.0 && lower_bound != usize::MAX {
-            upper_bound = i;
-        }
-        if upper_bound != usize::MAX && lower_bound != usize::MAX {
-            let temp_delta = thermal_list[upper_bound].0 - thermal_list[lower_bound].0;
-            let data_delta = thermal_list[upper_bound].1.R_th - thermal_list[lower_bound].1.R_th;
+    /// Compare with the output directory as committed in this git revision
+    #[arg(long, value_name = "REV", conflicts_with = "against")]
+    rev: Option<String>,
 
-            for j in (lower_bound + 1)..upper_bound {
-                thermal_list[j].1.R_th = data_delta / temp_delta * (thermal_list[j].0 - thermal_list[lower_bound].0) + thermal_list[lower_bound].1.R_th;
-            }
-            upper_bound = usize::MAX;
-            lower_bound = usize::MAX;
+    /// Relative change of a preset value that counts as regression
+    #[arg(long, default_value_t = 0.001)]
+    tolerance: f32,
+
+    /// Relative change of a csv curve row that counts as regression
+    #[arg(long, default_value_t = 0.01)]
+    curve_tolerance: f32,
+}
+
+#[derive(Args, Clone)]
+struct SimulateArgs {
+    file: PathBuf,
+
+    /// Csv of the absorbed heat flux: time in s, flux in W/m²
+    #[arg(long, value_name = "FILE", required_unless_present = "surface_temp")]
+    flux: Option<PathBuf>,
+
+    /// Csv of the surface temperature: time in s, temperature in K
+    #[arg(long, value_name = "FILE", conflicts_with = "flux")]
+    surface_temp: Option<PathBuf>,
+
+    /// Simulate the Max layers instead of the Min layers
+    #[arg(long)]
+    max: bool,
+
+    #[command(flatten)]
+    transient: TransientArgs,
+
+    /// Also run the single skin model of KSP with the computed preset & report its error against the layers,
+    /// needs --back-temp or --back-capacity
+    #[arg(long, requires = "back")]
+    skin_model: bool,
+
+    /// Highest back face temperature the substructure takes in K [default: Temperature Limit of the bottom layer]
+    #[arg(long, value_name = "K")]
+    back_limit: Option<f32>,
+}
+
+#[derive(Args, Clone)]
+struct OptimizeArgs {
+    file: PathBuf,
+
+    /// Layer to optimize with its thickness range in m, by position (1 = top layer) or material name, can be repeated
+    #[arg(long = "layer", value_name = "LAYER=MIN:MAX", required = true, value_parser = parse_layer_bounds)]
+    layers: Vec<LayerBounds>,
+
+    /// Csv of the absorbed heat flux: time in s, flux in W/m² [default: steady profile with the "Back Face" row of the TPS]
+    #[arg(long, value_name = "FILE")]
+    flux: Option<PathBuf>,
+
+    /// Csv of the surface temperature: time in s, temperature in K
+    #[arg(long, value_name = "FILE", conflicts_with = "flux")]
+    surface_temp: Option<PathBuf>,
+
+    /// Optimize the Max layers instead of the Min layers
+    #[arg(long)]
+    max: bool,
+
+    #[command(flatten)]
+    transient: TransientArgs,
+
+    /// Highest back face temperature the substructure takes in K [default: Temperature Limit of the bottom layer]
+    #[arg(long, value_name = "K")]
+    back_limit: Option<f32>,
+
+    /// Thicknesses are multiples of this in m
+    #[arg(long, default_value_t = 0.0001)]
+    resolution: f32,
+}
+
+/// Settings of the transient solver shared by simulate & optimize
+#[derive(Args, Clone)]
+struct TransientArgs {
+    /// Time step in s
+    #[arg(long, default_value_t = 1.0)]
+    time_step: f32,
+
+    /// Simulated time in s [default: end of the history]
+    #[arg(long)]
+    duration: Option<f32>,
+
+    /// Temperature of the stack at the start in K
+    #[arg(long, default_value_t = 300.0)]
+    initial_temp: f32,
+
+    /// Temperature the surface radiates to in K
+    #[arg(long, default_value_t = 300.0)]
+    ambient_temp: f32,
+
+    /// Hold the back face at this temperature in K instead of insulating it
+    #[arg(long, value_name = "K", group = "back")]
+    back_temp: Option<f32>,
+
+    /// Heat capacity of the substructure behind the back face in J/m²K, instead of insulating it
+    #[arg(long, value_name = "J/m²K", group = "back")]
+    back_capacity: Option<f32>,
+
+    /// Finite volume cells per layer
+    #[arg(long, default_value_t = 8)]
+    cells: usize,
+}
+impl TransientArgs {
+    fn settings(&self) -> TransientSettings {
+        TransientSettings {
+            time_step: self.time_step,
+            duration: self.duration.unwrap_or(0.0),
+            initial_temp: self.initial_temp,
+            ambient_temp: self.ambient_temp,
+            cells_per_layer: self.cells,
+            back_face: match (self.back_temp, self.back_capacity) {
+                (Some(temp), _)     => BackFace::Temperature(temp),
+                (_, Some(capacity)) => BackFace::HeatSink(capacity),
+                (None, None)        => BackFace::Adiabatic,
+            },
         }
     }
+}
 
-    upper_bound = usize::MAX;
-    lower_bound = usize::MAX;
-    for i in n_e..thermal_list.len() {
-        if thermal_list[i].1.e == 0.0 && lower_bound == usize::MAX && i > 0{
-            lower_bound = i-1;
-            n_e = lower_bound;
-        }
-        if thermal_list[i].1.e != 0.0 && lower_bound != usize::MAX {
-            upper_bound = i;
-        }
-        if upper_bound != usize::MAX && lower_bound != usize::MAX {
-            let temp_delta = thermal_list[upper_bound].0 - thermal_list[lower_bound].0;
-            let data_delta = thermal_list[upper_bound].1.e - thermal_list[lower_bound].1.e;
+/// `--layer` of optimize, the layer is resolved against the TPS later
+#[derive(Clone)]
+struct LayerBounds {
+    layer: String,
+    min: f32,
+    max: f32,
+}
 
-            for j in (lower_bound + 1)..upper_bound {
-                thermal_list[j].1.e = data_delta / temp_delta * (thermal_list[j].0 - thermal_list[lower_bound].0) + thermal_list[lower_bound].1.e;
-            }
-            upper_bound = usize::MAX;
-            lower_bound = usize::MAX;
-        }
+fn parse_layer_bounds(value: &str) -> Result<LayerBounds, String> {
+    let invalid = || format!("expected LAYER=MIN:MAX, got \"{}\"", value);
+    let (layer, range) = value.rsplit_once('=').ok_or_else(invalid)?;
+    let (min, max) = range.split_once(':').ok_or_else(invalid)?;
+    Ok(LayerBounds {
+        layer: layer.trim().to_string(),
+        min: min.trim().parse().map_err(|_err| invalid())?,
+        max: max.trim().parse().map_err(|_err| invalid())?,
+    })
+}
+
+fn parse_reference(value: &str) -> Result<ReferenceTemp, String> {
+    if let Some(path) = value.strip_prefix("pulse:") {
+        let history = read_history_csv(Path::new(path)).map_err(|err| err.to_string())?;
+        return Ok(ReferenceTemp::Pulse(HeatPulse { source: path.to_string(), history }));
     }
+    ReferenceTemp::parse(value).ok_or_else(|| format!("expected max, pulse:<CSV> or a fraction in (0, 1], got \"{}\"", value))
+}
 
-    // copy last non zero entry to all entries after
-    for i in n_cp..thermal_list.len() {
-        // not necessary, just double check
-        if thermal_list[i].1.cp == 0.0 {
-            thermal_list[i].1.cp = thermal_list[n_cp].1.cp;
-        }
+#[derive(Args, Clone, Default)]
+struct Filter {
+    /// Only build the TPS with this name, can be repeated
+    #[arg(long = "tps", value_name = "NAME")]
+    tps: Vec<String>,
+
+    /// Only build the part with this name, can be repeated
+    #[arg(long = "part", value_name = "NAME")]
+    part: Vec<String>,
+}
+impl Filter {
+    fn is_empty(&self) -> bool {
+        self.tps.is_empty() && self.part.is_empty()
     }
-    for i in n_r_th..thermal_list.len() {
-        // not necessary, just double check
-        if thermal_list[i].1.R_th == 0.0 {
-            thermal_list[i].1.R_th = thermal_list[n_r_th].1.R_th;
-        }
+
+    /// Parts named in the filter and those built only from the TPS in it
+    fn keeps(&self, part: &PartSpec) -> bool {
+        self.is_empty() || self.part.contains(&part.name)
+            || (!part.structures.is_empty() && part.structures.iter().all(|structure| self.tps.contains(&structure.name)))
     }
-    for i in n_e..thermal_list.len() {
-        // not necessary, just double check
-        if thermal_list[i].1.e == 0.0 {
-            thermal_list[i].1.e = thermal_list[n_e].1.e;
-        }
+
+    /// Filter names without a TPS or part of that name
+    fn unmatched(&self, tps_specs: &[TpsSpec], part_specs: &[PartSpec]) -> Vec<CalcError> {
+        let tps_names = tps_specs.iter().map(|spec| spec.name.as_str()).collect::<Vec<&str>>();
+        let part_names = part_specs.iter().map(|spec| spec.name.as_str()).collect::<Vec<&str>>();
+        [("TPS", &self.tps, tps_names), ("Part", &self.part, part_names)].into_iter()
+            .map(|(kind, wanted, names)| (kind, wanted.iter().filter(|name| !names.contains(&name.as_str())).cloned().collect::<Vec<String>>()))
+            .filter(|(_kind, names)| !names.is_empty())
+            .map(|(kind, names)| CalcError::NameNotFound { kind, names })
+            .collect()
     }
 }
 
-/// Adjust read values to thickness & density
-fn adjust_to_height ( height: f32, data: &Vec<DataTriplet> ) -> Vec<DataTriplet> {
-    let mut data_new = Vec::<DataTriplet>::new();
-    for row in data.iter() {
-        let mut data = row.thermal_data.clone();
-        if row.thermal_data.R_th != 0.0 {
-            data.R_th = height / row.thermal_data.R_th * 1000.0;
-        } else {
 
+/// Errors & warnings of a run, printed together at the end
+#[derive(Default)]
+struct Report {
+    errors: Vec<CalcError>,
+    warnings: Vec<Issue>,
+}
+impl Report {
+    /// Sort the issues of a validation pass in, the spec is only handed back if it has no errors
+    fn validated(&mut self, spec: TpsSpec) -> Option<TpsSpec> {
+        let mut valid = true;
+        for issue in validate_tps(&spec) {
+            match issue.severity {
+                Severity::Warning => self.warnings.push(issue),
+                Severity::Error => {valid = false; self.errors.push(CalcError::Invalid(issue))},
+            }
         }
-        data_new.push(DataTriplet{temp_part: row.temp_part, temp_sub_part: row.temp_sub_part, thermal_data: data});
+        valid.then_some(spec)
     }
-    data_new
-}
-/// expand list in to predefined range & steps and fill in the gaps
-fn fit_list(thermal_list: &Vec<DataTriplet>, ref_temp_list: &Vec<f32>) -> Vec<DataTriplet>{
-    //let mut data_adjusted: Vec<DataTriplet> = Vec::<DataTriplet>::with_capacity(ref_temp_list.len());
-    let mut data_adjusted = Vec::<DataTriplet>::new();
-    let mut index = 0;
-
-    for (i, temp) in ref_temp_list.iter().enumerate() {
-        if temp >= &thermal_list[0].temp_part{
-            index = i;
-            break;
+
+    /// Warn about `.info` sidecars in `directory` that don't parse
+    fn info_files(&mut self, directory: &Path) {
+        match check_info_files(directory) {
+            Ok(issues) => self.warnings.extend(issues),
+            Err(err) => self.errors.push(err),
         }
-        data_adjusted.push( DataTriplet{ temp_part: *temp, thermal_data: thermal_list[0].thermal_data, temp_sub_part: thermal_list[0].temp_sub_part * *temp / thermal_list[0].temp_part });
     }
 
-    // take two neighboring values and fill int for temperatures fitting in between
-    for (n, row) in thermal_list.iter().enumerate() {
-        if row.temp_part > *ref_temp_list.last().unwrap() {
-            break;
+    /// Write the margin report of a TPS & warn about layers above their Temperature Limit
+    fn margins(&mut self, tps: &TPS, path: &Path, out: &Path) -> Vec<LayerMargin> {
+        let margins = temperature_margins(tps);
+        self.warnings.extend(check_margins(&margins, path));
+        if let Err(err) = output_margins(&tps.name, &margins, out) {
+            self.errors.push(err);
         }
-        
-        let temp_delta = thermal_list[n+1].temp_part - row.temp_part;
-        let data_delta = thermal_list[n+1].thermal_data - row.thermal_data;
-        let temp_sub_part_delta = thermal_list[n+1].temp_sub_part - row.temp_sub_part;
-
-
-        for temp in ref_temp_list.iter().skip(index) {
-            if *temp == row.temp_part {
-                data_adjusted.push(DataTriplet { temp_part: row.temp_part, 
-                                                thermal_data: row.thermal_data,
-                                                temp_sub_part: row.temp_sub_part });
-                index += 1; 
-            }
-            if *temp > row.temp_part && *temp < thermal_list[n + 1].temp_part {
-                let data = data_delta / temp_delta * (temp - row.temp_part) + row.thermal_data;
-                    let temp_sub_part = temp_sub_part_delta / temp_delta * (temp - row.temp_part) + row.temp_sub_part;
-                    data_adjusted.push(DataTriplet { temp_part: *temp, 
-                                                     thermal_data: data,
-                                                     temp_sub_part: temp_sub_part });
-                index += 1;
-            }
+        margins
+    }
+
+    fn print_and_exit(self, strict: bool) {
+        // materials shared between TPS would otherwise be reported once per use
+        let mut warnings = self.warnings.iter().map(|issue| issue.to_string()).collect::<Vec<String>>();
+        warnings.sort();
+        warnings.dedup();
+        for warning in warnings.iter() {
+            println!("Warning {}", warning);
         }
-        if index == thermal_list.len() || index == ref_temp_list.len(){
-            break;
+
+        let mut errors = self.errors.iter().map(|err| err.to_string()).collect::<Vec<String>>();
+        errors.sort();
+        errors.dedup();
+        for error in errors.iter() {
+            println!("Error {}", error);
         }
-        if n + 2 == thermal_list.len() {
-            let data = data_delta / temp_delta * (ref_temp_list[index] - row.temp_part) + row.thermal_data;
-                    let temp_sub_part = temp_sub_part_delta / temp_delta * (ref_temp_list[index] - row.temp_part) + row.temp_sub_part;
-                    data_adjusted.push( DataTriplet { temp_part: ref_temp_list[index], 
-                                                     thermal_data: data,
-                                                     temp_sub_part: temp_sub_part });
-            index += 1;
-            break;
+        if !errors.is_empty() || (strict && !warnings.is_empty()) {
+            println!("{} error(s), {} warning(s)", errors.len(), warnings.len());
+            process::exit(1);
         }
-
     }
+}
 
-    // fill copy of last usable entry to fill the rest
-    for temp in ref_temp_list.iter().skip(index){
-        let temp_sub_part = data_adjusted.last().unwrap().temp_sub_part / data_adjusted.last().unwrap().temp_part * temp;
-        data_adjusted.push(DataTriplet { temp_part: *temp, thermal_data: data_adjusted.last().unwrap().thermal_data, temp_sub_part: temp_sub_part})
-    }
 
-    data_adjusted
+fn main() {
+    let cli = Cli::parse();
+    let mut report = Report::default();
+
+    let build_all = Command::Build(Filter::default());
+    let mut regression = false;
+    match cli.command.as_ref().unwrap_or(&build_all) {
+        Command::Build(filter) => build(&cli, filter, &mut report),
+        Command::Tps { file } => build_tps(&cli, file, &mut report),
+        Command::Part { file } => build_part(&cli, file, &mut report),
+        Command::Material { file } => build_material(&cli, file, &mut report),
+        Command::Read { file } => read_presets(file, &mut report),
+        Command::Diff(args) => regression = diff(&cli, args, &mut report),
+        Command::Simulate(args) => simulate_tps(&cli, args, &mut report),
+        Command::Optimize(args) => optimize_tps(&cli, args, &mut report),
+    }
+    report.print_and_exit(cli.strict);
+    if regression {
+        process::exit(1);
+    }
 }
 
-/// calculate the part values based on data from its structures
-fn calculate_part(part: &mut Part, temp_ref_list: &Vec<f32>) {
-    part.areal_density_min = 0.0;
-    part.areal_density_max = 0.0;
-    part.height_min = 0.0;
-    part.height_max = 0.0;
-
-    for (tps, portion, _data_min, _data_max) in part.tps_list.iter() {
-        part.areal_density_min += tps.areal_density_min * portion;
-        part.areal_density_max += tps.areal_density_max * portion;
-
-        part.height_min += tps.tickness_min * portion;
-        part.height_max += tps.tickness_max * portion;
-    }
-
-    for (i, temp) in temp_ref_list.iter().enumerate() {
-        let mut cp_min = 0.0;
-        let mut r_th_min = 0.0;
-        let mut e_min = 0.0;
-        let mut cp_max = 0.0;
-        let mut r_th_max = 0.0;
-        let mut e_max = 0.0;
-
-        for (tps, portion, data_min, data_max) in part.tps_list.iter() {
-            cp_min += data_min[i].thermal_data.cp * tps.areal_density_min / part.areal_density_min * portion * (data_min[i].temp_sub_part - TEMPERATURE_EQUALIZED) / (data_min[i].temp_part - TEMPERATURE_EQUALIZED);
-            r_th_min += portion * data_min[i].thermal_data.R_th;
-            e_min += data_min[i].thermal_data.e * portion;
-
-            cp_max += data_max[i].thermal_data.cp * tps.areal_density_max / part.areal_density_max * portion * (data_max[i].temp_sub_part - TEMPERATURE_EQUALIZED) / (data_max[i].temp_part - TEMPERATURE_EQUALIZED);
-            r_th_max += portion * data_max[i].thermal_data.R_th;
-            e_max += data_max[i].thermal_data.e * portion;
+fn temp_grid(cli: &Cli, report: &mut Report) -> Option<TempGrid> {
+    if let Some(range) = &cli.temp_range {
+        let (start, stop, step) = (range[0], range[1], range[2]);
+        if step <= 0.0 || stop < start {
+            Cli::command().error(ErrorKind::ValueValidation, "--temp-range needs START <= STOP and a positive STEP").exit();
         }
-        part.data_min.push(DataPair((*temp - 25.0) as f32, Data{cp: cp_min, R_th: 1.0 / r_th_min, e: e_min}));
-        part.data_max.push(DataPair((*temp - 25.0) as f32, Data{cp: cp_max, R_th: 1.0 / r_th_max, e: e_max}));
-    } 
-}
-
-/// calculate the structure values based on data from layer
-fn calc_tps_height_density(tps: &mut TPS) {
-    tps.areal_density_min = 0.0;
-    tps.tickness_min = 0.0;
-    tps.areal_density_max = 0.0;
-    tps.tickness_max = 0.0;
-    
-    for layer in tps.segments_min.iter() {
-        tps.areal_density_min += layer.areal_density; 
-        tps.tickness_min += layer.tickness;
+        return Some(TempGrid::from_range(start, stop, step));
     }
 
-    for layer in tps.segments_max.iter() {
-        tps.areal_density_max += layer.areal_density; 
-        tps.tickness_max += layer.tickness;
+    let temp_list = cli.temp_list.clone().unwrap_or(cli.library.join("Temp_List.csv"));
+    match read_temp_list_csv2(&temp_list) {
+        Ok(result) => Some(TempGrid::new(result)),
+        Err(err) => {report.errors.push(err); None},
     }
 }
 
-fn calc_tps_data(segments: &Vec<Segment>, temp_list: &Vec<f32>) -> Vec<DataPair> {
-
-    let mut data = Vec::<DataPair>::new();
-
-    for (i, temp) in temp_list.iter().enumerate() {    
-        let mut cp = 0.0;
-        let mut r_th = 0.0;
-        let mut e = 0.0;
+/// Read & validate all TPS of the library, broken files are skipped & reported
+fn load_library_tps(cli: &Cli, library: &MaterialLibrary, report: &mut Report) -> Vec<TpsSpec> {
+    let mut tps_specs = Vec::<TpsSpec>::new();
+    match get_files(&cli.library.join("tps"), "csv") {
+        Ok(paths) => for path in paths.iter() {
+            match load_tps_spec(path, library) {
+                Ok(spec) => tps_specs.extend(report.validated(spec)),
+                Err(mut errs) => report.errors.append(&mut errs),
+            }
+        },
+        Err(err) => report.errors.push(err),
+    }
+    report.info_files(&cli.library.join("tps"));
+    tps_specs
+}
 
-        for layer in segments.iter() {
-            cp += layer.data_tps_temp_mult[i].thermal_data.cp;
-            r_th += layer.data_tps_temp_mult[i].thermal_data.R_th;
+fn build(cli: &Cli, filter: &Filter, report: &mut Report) {
+    // read everything first, so all broken files get reported at once
+    let library = MaterialLibrary::new(&cli.library);
+    let tps_specs = load_library_tps(cli, &library, report);
+
+    let mut part_specs = Vec::<PartSpec>::new();
+    match get_files(&cli.library.join("part"), "csv") {
+        Ok(paths) => for path in paths.iter() {
+            match read_part_csv(path) {
+                Ok(spec) => part_specs.push(spec),
+                Err(err) => report.errors.push(err),
+            }
+        },
+        Err(err) => report.errors.push(err),
+    }
+    report.info_files(&cli.library.join("part"));
+    report.errors.extend(filter.unmatched(&tps_specs, &part_specs));
+    part_specs.retain(|spec| filter.keeps(spec));
+
+    let Some(temp_grid) = temp_grid(cli, report) else { return };
+    let format = cli.preset_format();
+    let mut presets = Vec::<Preset>::new();
+    let mut index = Vec::<IndexEntry>::new();
+    let mut tps_list = Vec::<TPS>::new();
+    for spec in tps_specs.iter() {
+        let write = filter.is_empty() || filter.tps.contains(&spec.name);
+        let needed = part_specs.iter().any(|part| part.structures.iter().any(|structure| structure.name == spec.name));
+        if !write && !needed {
+            continue;
+        }
 
-            if e <= 0.0 {
-                e = layer.data_tps_temp_mult[i].thermal_data.e;
+        let tps = compute_tps(spec, &temp_grid);
+        if write {
+            if let Err(err) = output_tps(&tps, &cli.out, &format) {
+                report.errors.push(err);
             }
+            report.margins(&tps, &spec.path, &cli.out);
+            presets.push(Preset::from_tps(&tps, format.reference.clone()));
+            index.push(IndexEntry::from_tps(&tps));
         }
-        data.push(DataPair(*temp, Data{cp, R_th: r_th, e: e}));
+        tps_list.push(tps);
     }
-    data
-}
 
+    for spec in part_specs.iter() {
+        let result = compute_part(spec, &tps_list, &temp_grid)
+            .and_then(|part| {
+                presets.push(Preset::from_part(&part, format.reference.clone()));
+                index.push(IndexEntry::from_part(&part));
+                output_part(part, &cli.out, &format)
+            });
+        if let Err(err) = result {
+            report.errors.push(err);
+        }
+    }
+    cli.output_combined(presets, report);
+    if let Err(err) = output_index(&index, &cli.out) {
+        report.errors.push(err);
+    }
+}
 
-/// multiplyer on component values based om assembly temperature & density
-fn tps_value_mult (assembly_density: f32, segment_density: f32, segment_data: &Vec<DataTriplet>) -> Vec<DataTriplet>{
-    let mut new_tripl = Vec::<DataTriplet>::new();
-    let density_frac = segment_density / assembly_density;
+fn build_tps(cli: &Cli, file: &Path, report: &mut Report) {
+    let library = MaterialLibrary::new(&cli.library);
+    let spec = match load_tps_spec(file, &library) {
+        Ok(result) => result,
+        Err(mut errs) => {report.errors.append(&mut errs); return},
+    };
+    let Some(spec) = report.validated(spec) else { return };
+    report.info_files(file.parent().filter(|directory| !directory.as_os_str().is_empty()).unwrap_or(Path::new(".")));
+    let Some(temp_grid) = temp_grid(cli, report) else { return };
+
+    let tps = compute_tps(&spec, &temp_grid);
+    if let Err(err) = output_tps(&tps, &cli.out, &cli.preset_format()) {
+        report.errors.push(err);
+    }
+    cli.output_combined(vec![Preset::from_tps(&tps, cli.reference.clone())], report);
 
-    for row in segment_data.iter() {
-        new_tripl.push(DataTriplet{temp_part: row.temp_part, temp_sub_part: row.temp_sub_part, 
-                        thermal_data: Data{cp: row.thermal_data.cp * row.temp_sub_part * density_frac / row.temp_part,
-                                            R_th: row.thermal_data.R_th, //prop_list.thermal_data.R_th *= prop_list.temp_sub_part / prop_list.temp_part;
-                                            e: row.thermal_data.e,
-        }})
+    println!("{:<4} {:>5}  {:<24} {:>8} {:>8} {:>8}", "", "Layer", "Material", "Limit", "Peak", "Margin");
+    let margins = report.margins(&tps, file, &cli.out);
+    for margin in margins.iter() {
+        println!("{:<4} {:>5}  {:<24} {:>8.1} {:>8.1} {:>8.1}",
+                 margin.section, margin.layer, margin.material, margin.temp_limit, margin.temp_peak, margin.margin);
+    }
+    for margin in margins.iter().filter(|margin| margin.temp_limit > 0.0 && margin.temp_above > margin.temp_limit) {
+        println!("Note: {} layer {} ({}) sits below a cold face of {:.0} K, above its Temperature Limit {} K",
+                 margin.section, margin.layer, margin.material, margin.temp_above, margin.temp_limit);
     }
-    new_tripl
 }
 
-fn map_component_data_to_assembly(assemb_temp_max: f32, comp_temp_max: f32, comp_data: &Vec<DataPair>, temp_list: &Vec<f32>) -> Vec<DataTriplet> {
-    let mut data_new = Vec::<DataTriplet>::new();
-    let temp_mult;
-    if comp_temp_max < assemb_temp_max {
-        temp_mult = (assemb_temp_max - TEMPERATURE_EQUALIZED) / (comp_temp_max - TEMPERATURE_EQUALIZED);
-    } else {
-        temp_mult = 1.0;
-    }
-    
-    for data in comp_data.iter() {
-        let temp_assemb = (data.0 - TEMPERATURE_EQUALIZED) * temp_mult + TEMPERATURE_EQUALIZED;
-        data_new.push(DataTriplet{temp_part: temp_assemb, thermal_data: data.1, temp_sub_part: data.0})
-    }
-    //data_new
-    fit_list(&data_new, &temp_list)
+fn build_part(cli: &Cli, file: &Path, report: &mut Report) {
+    let spec = match read_part_csv(file) {
+        Ok(result) => result,
+        Err(err) => {report.errors.push(err); return},
+    };
+    report.info_files(file.parent().filter(|directory| !directory.as_os_str().is_empty()).unwrap_or(Path::new(".")));
+    let library = MaterialLibrary::new(&cli.library);
+    let tps_specs = load_library_tps(cli, &library, report);
+    let Some(temp_grid) = temp_grid(cli, report) else { return };
+
+    let tps_list = tps_specs.iter()
+        .filter(|tps| spec.structures.iter().any(|structure| structure.name == tps.name))
+        .map(|tps| compute_tps(tps, &temp_grid))
+        .collect::<Vec<TPS>>();
+    let result = compute_part(&spec, &tps_list, &temp_grid)
+        .and_then(|part| {
+            cli.output_combined(vec![Preset::from_part(&part, cli.reference.clone())], report);
+            output_part(part, &cli.out, &cli.preset_format())
+        });
+    if let Err(err) = result {
+        report.errors.push(err);
+    }
 }
 
-/// Returns a new list with an averaged conductivity & insulation accross tickness, for given cold & Hot Side Temperature 
-pub fn avg_cp_k(lenght: f32, data_ref: &Vec<DataTriplet>, temp_max: f32, temp_min: f32, temp_list_5: &Vec<f32> ) -> Vec<DataTriplet>{
-    if temp_min == temp_max {
-        return data_ref.clone();
-    }
-
-    let mut data_out= data_ref.clone();
-    let mut steps = Vec::<(f32,f32,f32,f32,f32)>::new();
-
-    let temp_frac = temp_min / temp_max;
-
-
-    // smaller steps for smother curve, negating the effect of missing a step due to multiplication with temp_frac
-    let data = fit_list(&data_ref, &temp_list_5);    
-
-    // extrapolate d value for the rest
-    let q_ref = 1.0; // q = q1 = q2 = qi = Ti * di / ki; -> di = q * ki / Ti
-    let mut d_sum: f32 = 0.0;
-    for row in data.iter() {
-        let k: f32 = lenght / row.thermal_data.R_th;
-        let d = q_ref * k / row.temp_sub_part ;
-        d_sum += d;
-        steps.push((row.temp_sub_part ,k , d, d_sum, row.thermal_data.cp));
-    }
-    
-    for row in data_out.iter_mut() {
-        let mut i = 0;
-        let mut r_th = 0.0;
-        let mut cp = 0.0;
-        let mut d_sum = 0.0;
-        while i < steps.len() {
-            if steps[i].0 >= row.temp_sub_part * temp_frac && steps[i].0 <= row.temp_sub_part {
-                r_th += steps[i].2 / steps[i].1;
-                cp += steps[i].4 * steps[i].2;
-                d_sum += steps[i].2
-            }
-            i += 1;
-        }
+fn simulate_tps(cli: &Cli, args: &SimulateArgs, report: &mut Report) {
+    let library = MaterialLibrary::new(&cli.library);
+    let spec = match load_tps_spec(&args.file, &library) {
+        Ok(result) => result,
+        Err(mut errs) => {report.errors.append(&mut errs); return},
+    };
+    let Some(spec) = report.validated(spec) else { return };
+
+    let surface = match read_surface(&args.flux, &args.surface_temp) {
+        Some(Ok(surface)) => surface,
+        Some(Err(err)) => {report.errors.push(err); return},
+        None => unreachable!("clap requires one of them"),
+    };
+    let settings = args.transient.settings();
+
+    let (section, segments) = if args.max { ("Max", &spec.segments_max) } else { ("Min", &spec.segments_min) };
+    let transient = match simulate(segments, &surface, &settings) {
+        Ok(transient) => transient,
+        Err(err) => {report.errors.push(err); return},
+    };
+    let file_name = format!("{}_{}", spec.name, section.to_lowercase());
+    if let Err(err) = output_transient(&file_name, &transient, &cli.out) {
+        report.errors.push(err);
+    }
 
-        row.thermal_data.R_th = r_th / d_sum * lenght;
-        row.thermal_data.cp = cp / d_sum;
+    let peak = transient.peak_back_face();
+    let surface_peak = transient.points.iter().map(TransientPoint::surface).fold(f32::MIN, f32::max);
+    println!("{} {}: surface peak {:.0} K, back face peak {:.0} K at {} s", spec.name, section, surface_peak, peak.back_face(), peak.time);
+
+    let limit = args.back_limit.or(segments.last().map(|segment| segment.temp_max));
+    if let Some(limit) = limit.filter(|limit| peak.back_face() > *limit) {
+        report.warnings.push(Issue {
+            severity: Severity::Warning,
+            path: args.file.clone(),
+            line: None,
+            message: format!("{} back face reaches {:.0} K at {} s, {:.0} K above {} K", section, peak.back_face(), peak.time, peak.back_face() - limit, limit),
+        });
     }
-    data_out
-}
 
-fn tps_change_height(tps_ref: &TPS, new_height_min: f32, new_height_max: f32) -> TPS {
-    let mut tps = tps_ref.clone();
+    if args.skin_model {
+        let Some(temp_grid) = temp_grid(cli, report) else { return };
+        let tps = compute_tps(&spec, &temp_grid);
+        let result = PresetData::from_node(&Preset::from_tps(&tps, cli.reference.clone()).to_node(), &args.file)
+            .and_then(|preset| SkinModel::from_preset(&preset, args.max)
+                .ok_or(CalcError::Simulation(format!("preset {} lacks skin values", preset.name))))
+            .and_then(|skin| simulate_skin(&skin, &surface, &settings));
+        let skin = match result {
+            Ok(skin) => skin,
+            Err(err) => {report.errors.push(err); return},
+        };
+        if let Err(err) = output_skin_comparison(&file_name, &transient, &skin, &cli.out) {
+            report.errors.push(err);
+        }
+
+        let errors = compare(&transient, &skin);
+        let largest = |error: fn(&ModelError) -> f32| errors.iter().map(error).fold(0.0, |a: f32, b| if b.abs() > a.abs() { b } else { a });
+        let rms = |error: fn(&ModelError) -> f32| (errors.iter().map(|e| error(e).powi(2)).sum::<f32>() / errors.len() as f32).sqrt();
+        println!("skin model error: surface {:+.0} K (rms {:.0} K), back face {:+.0} K (rms {:.0} K)",
+                 largest(|e| e.surface), rms(|e| e.surface), largest(|e| e.back_face), rms(|e| e.back_face));
+    }
+}
 
-    if new_height_min == f32::INFINITY {
-        tps.tickness_min = tps.tickness_max;
-        tps.areal_density_min = tps.areal_density_max;
+/// Surface history of `--flux` or `--surface-temp`, `None` without either
+fn read_surface(flux: &Option<PathBuf>, surface_temp: &Option<PathBuf>) -> Option<Result<Surface, CalcError>> {
+    match (flux, surface_temp) {
+        (Some(file), _) => Some(read_history_csv(file).map(Surface::HeatFlux)),
+        (_, Some(file)) => Some(read_history_csv(file).map(Surface::Temperature)),
+        (None, None) => None,
+    }
+}
 
-        for (i, data) in tps.data_min.iter_mut().enumerate() {
-            data.1.cp = tps.data_max[i].1.cp;
-            data.1.R_th = tps.data_max[i].1.R_th;
+fn optimize_tps(cli: &Cli, args: &OptimizeArgs, report: &mut Report) {
+    let library = MaterialLibrary::new(&cli.library);
+    let spec = match load_tps_spec(&args.file, &library) {
+        Ok(result) => result,
+        Err(mut errs) => {report.errors.append(&mut errs); return},
+    };
+    let Some(mut spec) = report.validated(spec) else { return };
+
+    let case = match read_surface(&args.flux, &args.surface_temp) {
+        Some(Ok(surface)) => LoadCase::Transient { surface, settings: args.transient.settings() },
+        Some(Err(err)) => {report.errors.push(err); return},
+        None => match spec.back_boundary {
+            Some(back) => LoadCase::Steady { surface_temp: spec.temp, back },
+            None => {
+                report.errors.push(CalcError::Optimization(format!("{} needs --flux, --surface-temp or a \"Back Face\" row", spec.name)));
+                return;
+            },
+        },
+    };
+
+    let (section, segments) = if args.max { ("Max", &mut spec.segments_max) } else { ("Min", &mut spec.segments_min) };
+    let mut bounds = Vec::<ThicknessBounds>::new();
+    for layer in args.layers.iter() {
+        let index = match layer.layer.parse::<usize>() {
+            Ok(position) => position.checked_sub(1),
+            Err(_) => segments.iter().position(|segment| segment.name == layer.layer),
+        };
+        match index.filter(|i| *i < segments.len()) {
+            Some(i) => bounds.push(ThicknessBounds { layer: i, min: layer.min, max: layer.max }),
+            None => {
+                report.errors.push(CalcError::Optimization(format!("{} section of {} has no layer \"{}\"", section, spec.name, layer.layer)));
+                return;
+            },
         }
-    } else if new_height_min != f32::NEG_INFINITY {
-        let height_factor = (new_height_min - tps_ref.tickness_min) / (tps_ref.tickness_max - tps_ref.tickness_min);
+    }
+    let settings = OptimizeSettings {
+        back_limit: args.back_limit.or(segments.last().map(|segment| segment.temp_max)).filter(|limit| *limit > 0.0).unwrap_or(f32::INFINITY),
+        bounds,
+        resolution: args.resolution,
+    };
+
+    let before = stack_areal_density(segments);
+    let optimum = match optimize(segments, &case, &settings) {
+        Ok(optimum) => optimum,
+        Err(err) => {report.errors.push(err); return},
+    };
+    println!("{} {}: {:.2} kg/m² instead of {:.2} kg/m², back face peak {:.0} K, {} solver runs",
+             spec.name, section, optimum.areal_density, before, optimum.back_face, optimum.evaluations);
+    *segments = optimum.segments;
+    for segment in segments.iter() {
+        println!("  {}: {} m", segment.name, segment.tickness);
+    }
 
-        if height_factor > 0.001 && height_factor < 0.999 {
-            tps.tickness_min = (tps.tickness_max - tps.tickness_min) * height_factor + tps.tickness_min;
-            tps.areal_density_min = (tps.areal_density_max - tps.areal_density_min) * height_factor + tps.areal_density_min;
+    spec.name = format!("{}_Optimized", spec.name);
+    if let Err(err) = output_tps_csv(&spec, &cli.out.join("Optimized")) {
+        report.errors.push(err);
+    }
+}
 
-            for (i, data) in tps.data_min.iter_mut().enumerate() {
-                data.1.cp = (tps.data_max[i].1.cp - data.1.cp) * height_factor + data.1.cp;
-                data.1.R_th = (tps.data_max[i].1.R_th - data.1.R_th) * height_factor + data.1.R_th;
-            }
-        }
+fn build_material(cli: &Cli, file: &Path, report: &mut Report) {
+    let mut material = Segment { path: file.to_string_lossy().to_string(), ..Default::default() };
+    if let Err(err) = read_material_csv(&mut material) {
+        report.errors.push(err);
+        return;
     }
-    if new_height_max == f32::NEG_INFINITY {
-        tps.tickness_max = tps.tickness_min;
-        tps.areal_density_max = tps.areal_density_min;
+    fill_gaps_in_csv(&mut material.data_csv, material.interpolation);
+
+    let interpolation = material.interpolation;
+    println!("{}: Temperature Limit {} K, Density {} kg/m³, {} rows, interpolation {}/{}/{}",
+             material.name, material.temp_max, material.density, material.data_csv.len(),
+             interpolation.specific_heat.name(), interpolation.conductivity.name(), interpolation.emissivity.name());
+    if let Err(err) = output_material(&material, &cli.out) {
+        report.errors.push(err);
+    }
+}
 
-        for (i, data) in tps.data_max.iter_mut().enumerate() {
-            data.1.cp = tps.data_min[i].1.cp;
-            data.1.R_th = tps.data_min[i].1.R_th;
+fn read_presets(file: &Path, report: &mut Report) {
+    let presets = match read_preset_cfg(file) {
+        Ok(result) => result,
+        Err(err) => {report.errors.push(err); return},
+    };
+
+    let value = |value: Option<f32>| value.map_or("-".to_string(), |value| value.to_string());
+    println!("{:<24} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}", "Name", "Max Temp", "Height", "Mass", "Cp", "Insulance", "Emissivity");
+    for preset in presets.iter() {
+        println!("{:<24} {:>8} {:>10} {:>10} {:>10} {:>10} {:>10}", preset.name, value(preset.temp),
+                 value(preset.tickness_min), value(preset.areal_density_min), value(preset.cp_min), value(preset.insulance_min), value(preset.emissive_const));
+        if preset.tickness_max.is_some() && preset.tickness_max != preset.tickness_min {
+            println!("{:<24} {:>8} {:>10} {:>10} {:>10} {:>10}", "", "",
+                     value(preset.tickness_max), value(preset.areal_density_max), value(preset.cp_max), value(preset.insulance_max));
         }
-    } else if new_height_max != f32::INFINITY {
-        let height_factor = (new_height_max - tps_ref.tickness_min) / (tps_ref.tickness_max - tps_ref.tickness_min);
-
-        if height_factor < 0.999 && height_factor > 0.001 {
-            tps.tickness_max = (tps.tickness_max - tps.tickness_min) * height_factor + tps.tickness_min;
-            tps.areal_density_max = (tps.areal_density_max - tps.areal_density_min) * height_factor + tps.areal_density_min;
+    }
+}
 
-            for (i, data) in tps.data_max.iter_mut().enumerate() {
-                data.1.cp = (data.1.cp - tps.data_min[i].1.cp) * height_factor + tps.data_min[i].1.cp;
-                data.1.R_th = (data.1.R_th - tps.data_min[i].1.R_th) * height_factor + tps.data_min[i].1.R_th;
-            }
-        }
+/// Returns if any preset changed beyond the tolerances
+fn diff(cli: &Cli, args: &DiffArgs, report: &mut Report) -> bool {
+    let scratch = env::temp_dir().join(format!("ro_materials_diff_{}", process::id()));
+    let fresh_out = scratch.join("new");
+    let fresh = Cli { out: fresh_out.clone(), patch: None, combined: None, ..cli.clone() };
+    build(&fresh, &Filter::default(), report);
+
+    let baseline = match (&args.against, &args.rev) {
+        (Some(against), _) => Baseline::Directory(against.clone()),
+        (None, Some(rev)) => Baseline::Revision { rev: rev.clone(), directory: cli.out.clone() },
+        (None, None) => Baseline::Directory(cli.out.clone()),
+    };
+    let tolerance = Tolerance { preset: args.tolerance, curve: args.curve_tolerance };
+    let result = diff_against(&baseline, &fresh_out, &scratch.join("old"), tolerance);
+    let _ = fs::remove_dir_all(&scratch);
+    match result {
+        Ok(output_diff) => {
+            println!("{}", output_diff);
+            output_diff.is_regression()
+        },
+        Err(err) => {report.errors.push(err); false},
     }
-    tps
-}
\ No newline at end of file
+}
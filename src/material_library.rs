@@ -0,0 +1,69 @@
+use super::error::CalcError;
+use super::read_write::{get_files, read_material_name};
+use super::validate::suggest;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Finds the material file a TPS layer refers to.
+///
+/// A reference is either a path or the "Name" entry of a material file in `<root>/materials`.
+/// Paths are tried relative to the TPS file, the library root, `<root>/materials`
+/// and at last the working directory, so older files with "bib/materials/..." keep working.
+/// A name shared by several files is ambiguous, those have to be referred to by path.
+#[derive(Debug, Clone, Default)]
+pub struct MaterialLibrary {
+    pub root: PathBuf,
+    /// files of each name, sorted
+    by_name: HashMap<String, Vec<PathBuf>>,
+}
+
+impl MaterialLibrary {
+    /// Index all materials in `<root>/materials` by their name.
+    /// Unreadable files are left out, they get reported once a TPS uses them by path.
+    pub fn new(root: &Path) -> Self {
+        let mut by_name = HashMap::<String, Vec<PathBuf>>::new();
+
+        for path in get_files(&root.join("materials"), "csv").unwrap_or_default() {
+            if let Ok(name) = read_material_name(&path) {
+                by_name.entry(name).or_default().push(path);
+            }
+        }
+        MaterialLibrary { root: root.to_path_buf(), by_name }
+    }
+
+    /// Path of the material `reference` used in the TPS file at `tps_path`
+    pub fn resolve(&self, reference: &str, tps_path: &Path) -> Result<PathBuf, CalcError> {
+        let reference_path = Path::new(reference);
+        let tps_directory = tps_path.parent().unwrap_or(Path::new(""));
+
+        let mut candidates = vec![tps_directory.join(reference_path), self.root.join(reference_path)];
+        if let Some(file_name) = reference_path.file_name() {
+            candidates.push(self.root.join("materials").join(file_name));
+        }
+        candidates.push(reference_path.to_path_buf());
+
+        if let Some(path) = candidates.into_iter().find(|path| path.is_file()) {
+            return Ok(path);
+        }
+        match self.by_name.get(reference).map(Vec::as_slice) {
+            Some([path]) => Ok(path.clone()),
+            Some(files)  => Err(CalcError::AmbiguousMaterial { path: tps_path.to_path_buf(), reference: reference.to_string(), files: files.to_vec() }),
+            None         => Err(CalcError::MaterialNotFound {
+                path: tps_path.to_path_buf(),
+                reference: reference.to_string(),
+                suggestion: self.suggest(reference).map(str::to_string),
+            }),
+        }
+    }
+
+    /// Material name closest to the misspelled `reference`
+    pub fn suggest(&self, reference: &str) -> Option<&str> {
+        let mut names = self.by_name.keys().map(String::as_str).collect::<Vec<&str>>();
+        // sorted, so a tie always suggests the same
+        names.sort();
+        suggest(reference, &names)
+    }
+}
@@ -0,0 +1,223 @@
+//! Minimum mass thicknesses of the layers of a TPS.
+//!
+//! The stack has to keep its back face below a limit & every layer below its material Temperature Limit,
+//! checked either with a heating history through [`simulate`] or a soak through [`steady_profile`].
+//! Thinner layers never run cooler, so each layer is first shrunk as far as it holds on its own,
+//! then thickness is traded between pairs of layers while that saves mass.
+
+use super::data_holder::*;
+use super::error::CalcError;
+use super::steady::{apply_steady_profile, steady_profile, BackBoundary};
+use super::transient::{simulate, Surface, TransientSettings};
+
+/// What the stack has to withstand
+#[derive(Debug, Clone)]
+pub enum LoadCase {
+    /// heating history, run through [`simulate`]
+    Transient { surface: Surface, settings: TransientSettings },
+    /// surface held at `surface_temp` K until steady, see [`steady_profile`]
+    Steady { surface_temp: f32, back: BackBoundary },
+}
+
+/// Thickness range of one layer, in m
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThicknessBounds {
+    /// index into the segments, top layer first
+    pub layer: usize,
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct OptimizeSettings {
+    /// K the back face may reach
+    pub back_limit: f32,
+    /// layers to optimize, all others keep their thickness
+    pub bounds: Vec<ThicknessBounds>,
+    /// m, thicknesses are multiples of it
+    pub resolution: f32,
+}
+
+impl Default for OptimizeSettings {
+    fn default() -> Self {
+        OptimizeSettings { back_limit: f32::INFINITY, bounds: Vec::new(), resolution: 0.0001 }
+    }
+}
+
+/// Lightest stack found
+#[derive(Debug, Clone)]
+pub struct Optimum {
+    /// the input segments with optimized thicknesses, a steady load case also sets their hot & cold sides
+    pub segments: Vec<Segment>,
+    /// kg/m²
+    pub areal_density: f32,
+    /// K, peak of the back face
+    pub back_face: f32,
+    /// number of solver runs
+    pub evaluations: usize,
+}
+
+/// Areal density of a stack in kg/m², like in [`calc_segment`](crate::calc_segment)
+pub fn stack_areal_density(segments: &[Segment]) -> f32 {
+    segments.iter().map(|segment| (segment.density * segment.tickness + segment.additive_areal_weight) * segment.portion).sum()
+}
+
+/// Peak temperature of every face over the load case, surface first. Layers without thickness have no faces
+fn face_peaks(segments: &[Segment], case: &LoadCase) -> Result<Vec<f32>, CalcError> {
+    match case {
+        LoadCase::Transient { surface, settings } => {
+            let transient = simulate(segments, surface, settings)?;
+            let mut peaks = transient.points[0].faces.clone();
+            for point in transient.points.iter() {
+                for (peak, temp) in peaks.iter_mut().zip(point.faces.iter()) {
+                    *peak = peak.max(*temp);
+                }
+            }
+            Ok(peaks)
+        },
+        LoadCase::Steady { surface_temp, back } => {
+            let thick = segments.iter().filter(|segment| is_thick(segment)).cloned().collect::<Vec<Segment>>();
+            Ok(steady_profile(&thick, *surface_temp, *back)?.faces)
+        },
+    }
+}
+
+fn is_thick(segment: &Segment) -> bool {
+    segment.tickness * segment.portion > 0.0
+}
+
+/// Search state, thicknesses as multiples of the resolution
+struct Search<'a> {
+    segments: Vec<Segment>,
+    case: &'a LoadCase,
+    back_limit: f32,
+    /// grid steps per m
+    steps: f32,
+    evaluations: usize,
+}
+
+impl Search<'_> {
+    fn set(&mut self, layer: usize, units: i64) {
+        self.segments[layer].tickness = units as f32 / self.steps;
+    }
+
+    /// Why the current stack fails, `None` if it holds
+    fn violation(&mut self) -> Result<Option<String>, CalcError> {
+        self.evaluations += 1;
+        let faces = face_peaks(&self.segments, self.case)?;
+
+        let mut face = 0;
+        for segment in self.segments.iter() {
+            let below = if is_thick(segment) { face + 1 } else { face };
+            let peak = faces[face].max(faces[below]);
+            if segment.temp_max > 0.0 && peak > segment.temp_max {
+                return Ok(Some(format!("{} reaches {:.0} K, above its Temperature Limit of {} K", segment.name, peak, segment.temp_max)));
+            }
+            face = below;
+        }
+        let back_face = faces[faces.len() - 1];
+        if back_face > self.back_limit {
+            return Ok(Some(format!("the back face reaches {:.0} K, above {} K", back_face, self.back_limit)));
+        }
+        Ok(None)
+    }
+
+    /// Set `layer` to the thinnest thickness in `[low, high]` that holds, `false` if not even `high` does
+    fn thinnest(&mut self, layer: usize, mut low: i64, mut high: i64) -> Result<bool, CalcError> {
+        self.set(layer, high);
+        if self.violation()?.is_some() {
+            return Ok(false);
+        }
+        self.set(layer, low);
+        if self.violation()?.is_none() {
+            return Ok(true);
+        }
+        // low fails, high holds
+        while high - low > 1 {
+            let mid = (low + high) / 2;
+            self.set(layer, mid);
+            if self.violation()?.is_some() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        self.set(layer, high);
+        Ok(true)
+    }
+}
+
+/// Optimize the thicknesses of the layers named in `settings.bounds`, `segments` need their material data loaded
+pub fn optimize(segments: &[Segment], case: &LoadCase, settings: &OptimizeSettings) -> Result<Optimum, CalcError> {
+    if settings.bounds.is_empty() {
+        return Err(CalcError::Optimization("no layer to optimize".to_string()));
+    }
+    if !(settings.resolution > 0.0 && settings.resolution.is_finite()) {
+        return Err(CalcError::Optimization(format!("resolution {} m is not positive", settings.resolution)));
+    }
+    let steps = (1.0 / settings.resolution).round();
+    let mut ranges = Vec::<(usize, i64, i64)>::new();
+    for bounds in settings.bounds.iter() {
+        let (min, max) = ((bounds.min * steps).ceil() as i64, (bounds.max * steps).floor() as i64);
+        if bounds.layer >= segments.len() {
+            return Err(CalcError::Optimization(format!("layer {} doesn't exist, the stack has {}", bounds.layer + 1, segments.len())));
+        }
+        if min <= 0 || min > max {
+            return Err(CalcError::Optimization(format!("bounds {} m to {} m of layer {} hold no thickness", bounds.min, bounds.max, bounds.layer + 1)));
+        }
+        ranges.push((bounds.layer, min, max));
+    }
+
+    let mut search = Search { segments: segments.to_vec(), case, back_limit: settings.back_limit, steps, evaluations: 0 };
+    for (layer, _, max) in ranges.iter() {
+        search.set(*layer, *max);
+    }
+    if let Some(reason) = search.violation()? {
+        return Err(CalcError::Optimization(format!("not even the thickest stack holds, {}", reason)));
+    }
+    let mut units = ranges.iter().map(|(_, _, max)| *max).collect::<Vec<i64>>();
+
+    // shrink every layer on its own, the others can't get any thinner afterwards
+    for (k, (layer, min, _)) in ranges.iter().enumerate() {
+        search.thinnest(*layer, *min, units[k])?;
+        units[k] = (search.segments[*layer].tickness * steps).round() as i64;
+    }
+
+    // trade: thin layer i by `step`, thicken layer j as little as needed, keep it if the stack got lighter
+    let mut step = ranges.iter().map(|(_, min, max)| (max - min) / 4).max().unwrap_or(0).max(1);
+    while ranges.len() > 1 {
+        let mut improved = false;
+        for i in 0..ranges.len() {
+            for j in 0..ranges.len() {
+                let ((layer_i, min_i, _), (layer_j, _, max_j)) = (ranges[i], ranges[j]);
+                if i == j || units[i] == min_i {
+                    continue;
+                }
+                let density = stack_areal_density(&search.segments);
+                search.set(layer_i, (units[i] - step).max(min_i));
+                let holds = search.thinnest(layer_j, units[j], max_j)?;
+                if holds && stack_areal_density(&search.segments) < density - 1e-6 {
+                    units[i] = (search.segments[layer_i].tickness * steps).round() as i64;
+                    units[j] = (search.segments[layer_j].tickness * steps).round() as i64;
+                    improved = true;
+                } else {
+                    search.set(layer_i, units[i]);
+                    search.set(layer_j, units[j]);
+                }
+            }
+        }
+        if !improved {
+            if step == 1 {
+                break;
+            }
+            step /= 2;
+        }
+    }
+
+    let mut segments = search.segments;
+    if let LoadCase::Steady { surface_temp, back } = case {
+        apply_steady_profile(&mut segments, *surface_temp, *back)?;
+    }
+    let back_face = face_peaks(&segments, case)?.last().copied().unwrap_or(0.0);
+    Ok(Optimum { areal_density: stack_areal_density(&segments), back_face, evaluations: search.evaluations + 1, segments })
+}
@@ -0,0 +1,440 @@
+use super::ablator::AblatorParams;
+use super::config_node::ConfigNode;
+use super::data_holder::*;
+use super::error::CalcError;
+use super::info::Info;
+use super::property_table::PropertyTable;
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// ModuleManager operation a preset is written with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOp {
+    /// new node, `ROThermal_PRESET`
+    Create,
+    /// edit an existing preset, `@ROThermal_PRESET[name]` with `@key = value`
+    Edit,
+    /// edit or create, `%ROThermal_PRESET[name]` with `%key = value`
+    Replace,
+}
+
+/// How presets are written, the default is a bare `ROThermal_PRESET` node
+#[derive(Debug, Clone, Default)]
+pub struct PresetFormat {
+    pub patch: Option<PatchOp>,
+    /// mods for the `:NEEDS[...]` clause
+    pub needs: Vec<String>,
+    /// mod for the `:FOR[...]` clause
+    pub for_mod: Option<String>,
+    /// write the temperature dependent values as FloatCurve nodes instead of their value at `reference`
+    pub curves: bool,
+    pub reference: ReferenceTemp,
+}
+
+/// Temperature the single values of a preset are taken at, curves are interpolated there
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ReferenceTemp {
+    /// `skinMaxTemp`
+    #[default]
+    Max,
+    /// this fraction of `skinMaxTemp`
+    Fraction(f32),
+    /// mean over the surface temperatures of a heating history, each weighted by the heat radiated at it over the time spent there
+    Pulse(HeatPulse),
+}
+
+/// Surface temperature history of a heat pulse, e.g. a reentry
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatPulse {
+    /// where the history comes from, named in the preset comment
+    pub source: String,
+    /// K over time in s
+    pub history: PropertyTable<f32>,
+}
+
+impl ReferenceTemp {
+    /// K between the samples of [`ReferenceTemp::Pulse`]
+    pub const PULSE_STEP: f32 = 5.0;
+
+    /// "max" or a fraction like "0.8", a [`ReferenceTemp::Pulse`] needs its history
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "max"    => Some(ReferenceTemp::Max),
+            fraction => fraction.parse::<f32>().ok()
+                .filter(|fraction| *fraction > 0.0 && *fraction <= 1.0)
+                .map(ReferenceTemp::Fraction),
+        }
+    }
+
+    /// Value of `curve` for a preset of max temperature `temp_max`, 0 for an empty curve
+    pub fn value(&self, curve: &PropertyTable<f32>, temp_max: f32) -> f32 {
+        let at = |temp: f32| curve.at(temp).unwrap_or(0.0);
+        match self {
+            ReferenceTemp::Max                => at(temp_max),
+            ReferenceTemp::Fraction(fraction) => at(temp_max * fraction),
+            ReferenceTemp::Pulse(pulse)       => {
+                // ∫ value(T) T⁴ dt / ∫ T⁴ dt, midpoints of steps of at most PULSE_STEP along each segment of the history
+                let (mut sum, mut weights) = (0.0, 0.0);
+                let rows = pulse.history.iter().zip(pulse.history.iter().skip(1));
+                for ((time, temp), (next_time, next_temp)) in rows {
+                    let steps = ((next_temp - temp).abs() / Self::PULSE_STEP).ceil().max(1.0) as usize;
+                    let dt = (next_time - time) / steps as f32;
+                    for i in 0..steps {
+                        let temp = temp + (next_temp - temp) * (i as f32 + 0.5) / steps as f32;
+                        // the skin doesn't get hotter than skinMaxTemp
+                        let temp = temp.min(temp_max);
+                        sum += at(temp) * temp.powi(4) * dt;
+                        weights += temp.powi(4) * dt;
+                    }
+                }
+                if weights > 0.0 { sum / weights } else { at(temp_max) }
+            },
+        }
+    }
+
+    /// First line of the comment block, how the values were taken
+    pub fn describe(&self, temp_max: f32) -> String {
+        match self {
+            ReferenceTemp::Max                => format!("Values at {} K, skinMaxTemp", temp_max),
+            ReferenceTemp::Fraction(fraction) => format!("Values at {} K, {} of skinMaxTemp", temp_max * fraction, fraction),
+            ReferenceTemp::Pulse(pulse)       => format!("Values averaged over the heat pulse {} up to {} K, weighted by T^4 & time", pulse.source, temp_max),
+        }
+    }
+}
+
+/// `ROThermal_PRESET` values of a TPS or part
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub name: String,
+    /// key & value pairs, groups are separated by an empty line
+    pub groups: Vec<Vec<(&'static str, String)>>,
+    /// written as `//` comments behind the node, empty lines stay empty
+    pub comments: Vec<String>,
+    /// temperature dependence of keys in `groups`, see [`PresetFormat::curves`]
+    pub curves: Vec<(&'static str, PropertyTable<f32>)>,
+}
+
+impl Preset {
+    /// Preset of a TPS, the single values are taken at `reference`
+    pub fn from_tps(tps: &TPS, reference: ReferenceTemp) -> Self {
+        let curves = vec![
+            ("emissiveConstant", tps.data_min.map(|_temp, data| data.e)),
+            ("skinSpecificHeatCapacity", tps.data_min.map(|_temp, data| data.cp)),
+            ("thermalInsulance", tps.data_min.map(|_temp, data| data.R_th)),
+            ("skinSpecificHeatCapacityMax", tps.data_max.map(|_temp, data| data.cp)),
+            ("thermalInsulanceMax", tps.data_max.map(|_temp, data| data.R_th)),
+        ];
+        let value = |i: usize| reference.value(&curves[i].1, tps.temp).to_string();
+
+        let mut comments = vec![reference.describe(tps.temp), "".to_string()];
+        comments.extend(["Min: ".to_string(), "Segment, Height".to_string()]);
+        comments.extend(tps.segments_min.iter().map(|segment| format!("{}, {}", segment.name, segment.tickness)));
+        comments.extend(["".to_string(), "Max: ".to_string(), "Segment, Height".to_string()]);
+        comments.extend(tps.segments_max.iter().map(|segment| format!("{}, {}", segment.name, segment.tickness)));
+        comments.extend(info_comments(tps.info.as_ref()));
+        comments.extend(reference_comments(tps.segments_min.iter().chain(tps.segments_max.iter())));
+
+        let mut preset = Preset {
+            name: tps.name.clone(),
+            groups: vec![
+                vec![
+                    ("name", tps.name.clone()),
+                    ("description", tps.description.clone()),
+                    ("type", "Skin".to_string()),
+                ],
+                vec![
+                    ("skinMaxTemp", tps.temp.to_string()),
+                    ("emissiveConstant", value(0)),
+                    ("absorptiveConstant", tps.absorbation_const.unwrap_or(0.0).to_string()),
+                ],
+                vec![
+                    ("skinHeightMin", format!("{:0.4}", tps.tickness_min)),
+                    ("skinMassPerArea", tps.areal_density_min.to_string()),
+                    ("skinSpecificHeatCapacity", value(1)),
+                    ("thermalInsulance", value(2)),
+                ],
+                vec![
+                    ("skinHeightMax", format!("{:0.4}", tps.tickness_max)),
+                    ("skinMassPerAreaMax", tps.areal_density_max.to_string()),
+                    ("skinSpecificHeatCapacityMax", value(3)),
+                    ("thermalInsulanceMax", value(4)),
+                ],
+                vec![
+                    ("disableModAblator", tps.ablator.is_none().to_string()),
+                    ("costPerArea", tps.cost_per_area_min.to_string()),
+                    ("costPerAreaMax", tps.cost_per_area_max.to_string()),
+                ],
+            ],
+            comments,
+            curves,
+        };
+        if let Some(ablator) = &tps.ablator {
+            preset.groups.push(ablator_group(ablator, tps.areal_density_min, tps.areal_density_max));
+        }
+        preset
+    }
+
+    /// Preset of a part, the single values are taken at `reference`
+    pub fn from_part(part: &Part, reference: ReferenceTemp) -> Self {
+        let curves = vec![
+            ("emissiveConstant", part.data_min.map(|_temp, data| data.e)),
+            ("skinSpecificHeatCapacity", part.data_min.map(|_temp, data| data.cp)),
+            ("thermalInsulance", part.data_min.map(|_temp, data| f32::powf(data.R_th, -1.0))),
+            ("skinSpecificHeatCapacityMax", part.data_max.map(|_temp, data| data.cp)),
+            ("thermalInsulanceMax", part.data_max.map(|_temp, data| f32::powf(data.R_th, -1.0))),
+        ];
+        let value = |i: usize| reference.value(&curves[i].1, part.temp).to_string();
+
+        let mut comments = vec![reference.describe(part.temp), "".to_string()];
+        comments.push("Segment, Portion, Min Height, Max Height".to_string());
+        comments.extend(part.tps_list.iter().map(|structure| {
+            format!("{}, {}, {}, {}", structure.0.name, structure.1, structure.0.tickness_min, structure.0.tickness_max)
+        }));
+        comments.extend(info_comments(part.info.as_ref()));
+        comments.extend(reference_comments(part.tps_list.iter()
+            .flat_map(|structure| structure.0.segments_min.iter().chain(structure.0.segments_max.iter()))));
+
+        let mut preset = Preset {
+            name: part.name.clone(),
+            groups: vec![
+                vec![
+                    ("name", part.name.clone()),
+                    ("description", part.description.clone()),
+                    ("type", "Skin".to_string()),
+                ],
+                vec![
+                    ("skinMaxTemp", part.temp.to_string()),
+                    ("emissiveConstant", value(0)),
+                    ("absorptiveConstant", part.absorbation_const.to_string()),
+                ],
+                vec![
+                    ("skinHeightMin", format!("{:0.4}", part.height_min)),
+                    ("skinMassPerArea", part.areal_density_min.to_string()),
+                    ("skinSpecificHeatCapacity", value(1)),
+                    ("thermalInsulance", value(2)),
+                ],
+                vec![
+                    ("skinHeightMax", format!("{:0.4}", part.height_max)),
+                    ("skinMassPerAreaMax", part.areal_density_max.to_string()),
+                    ("skinSpecificHeatCapacityMax", value(3)),
+                    ("thermalInsulanceMax", value(4)),
+                ],
+                vec![
+                    ("disableModAblator", (!part.has_ablator).to_string()),
+                    ("costPerArea", part.cost_per_area_min.to_string()),
+                    ("costPerAreaMax", part.cost_per_area_max.to_string()),
+                ],
+            ],
+            comments,
+            curves,
+        };
+        if let Some(ablator) = &part.ablator {
+            preset.groups.push(ablator_group(ablator, part.areal_density_min, part.areal_density_max));
+        }
+        preset
+    }
+
+    /// Node of the preset, as it is read back from its cfg
+    pub fn to_node(&self) -> ConfigNode {
+        ConfigNode {
+            name: "ROThermal_PRESET".to_string(),
+            values: self.groups.iter().flatten().map(|(key, value)| (key.to_string(), value.clone())).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Node header, e.g. `@ROThermal_PRESET[HRSI]:NEEDS[ROThermal]:FOR[ROLibrary]`
+    pub fn header(&self, format: &PresetFormat) -> String {
+        let mut header = match format.patch {
+            None | Some(PatchOp::Create) => "ROThermal_PRESET".to_string(),
+            Some(PatchOp::Edit)          => format!("@ROThermal_PRESET[{}]", self.name),
+            Some(PatchOp::Replace)       => format!("%ROThermal_PRESET[{}]", self.name),
+        };
+        if format.patch.is_some() {
+            if !format.needs.is_empty() {
+                header += &format!(":NEEDS[{}]", format.needs.join(","));
+            }
+            if let Some(for_mod) = &format.for_mod {
+                header += &format!(":FOR[{}]", for_mod);
+            }
+        }
+        header
+    }
+
+    pub fn write(&self, file: &mut impl Write, format: &PresetFormat) -> io::Result<()> {
+        let operator = match format.patch {
+            None | Some(PatchOp::Create) => "",
+            Some(PatchOp::Edit)          => "@",
+            Some(PatchOp::Replace)       => "%",
+        };
+
+        let is_curve = |key: &str| format.curves && self.curves.iter().any(|(curve, _table)| *curve == key);
+
+        writeln!(file, "{}\n{{", self.header(format))?;
+        for (i, group) in self.groups.iter().enumerate() {
+            if i > 0 {
+                writeln!(file)?;
+            }
+            for (key, value) in group.iter().filter(|(key, _value)| !is_curve(key)) {
+                writeln!(file, "    {}{} = {}", operator, key, value)?;
+            }
+        }
+        if format.curves {
+            for (key, table) in self.curves.iter() {
+                writeln!(file)?;
+                // patches drop the value or curve the preset had before
+                if format.patch.is_some() {
+                    writeln!(file, "    !{} = delete\n    !{} {{}}", key, key)?;
+                }
+                writeln!(file, "    {}\n    {{", key)?;
+                for (temp, value) in table.iter() {
+                    writeln!(file, "        key = {} {}", temp, value)?;
+                }
+                writeln!(file, "    }}")?;
+            }
+        }
+        writeln!(file, "}}")?;
+
+        for comment in self.comments.iter() {
+            if comment.is_empty() {
+                writeln!(file)?;
+            } else {
+                writeln!(file, "// {}", comment)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `ModuleAblator` values with the mass left after reentry, once the ablative resource is gone
+fn ablator_group(ablator: &AblatorParams, areal_density_min: f32, areal_density_max: f32) -> Vec<(&'static str, String)> {
+    vec![
+        ("ablativeResource", "Ablator".to_string()),
+        ("ablativeResourcePerArea", ablator.resource_per_area_min.to_string()),
+        ("ablativeResourcePerAreaMax", ablator.resource_per_area_max.to_string()),
+        ("ablationTempThresh", ablator.temp_threshold.to_string()),
+        ("lossExp", ablator.loss_exp.to_string()),
+        ("pyrolysisLossFactor", ablator.pyrolysis_loss_factor.to_string()),
+        ("reentryMassPerArea", AblatorParams::reentry_mass(areal_density_min, ablator.resource_per_area_min).to_string()),
+        ("reentryMassPerAreaMax", AblatorParams::reentry_mass(areal_density_max, ablator.resource_per_area_max).to_string()),
+    ]
+}
+
+/// Lines of the `.info` sidecar after an empty line, empty without sidecar
+fn info_comments(info: Option<&Info>) -> Vec<String> {
+    let Some(info) = info.filter(|info| !info.lines().is_empty()) else { return Vec::new() };
+    let mut comments = vec!["".to_string()];
+    comments.extend(info.lines());
+    comments
+}
+
+/// "References:" block of the cited materials among `segments`, each material once. Empty if none is cited
+fn reference_comments<'a>(segments: impl Iterator<Item = &'a Segment>) -> Vec<String> {
+    let mut cited = Vec::<&Segment>::new();
+    for segment in segments {
+        if !segment.citation.is_empty() && !cited.iter().any(|other| other.name == segment.name) {
+            cited.push(segment);
+        }
+    }
+    if cited.is_empty() {
+        return Vec::new();
+    }
+
+    let mut comments = vec!["".to_string(), "References:".to_string()];
+    for segment in cited {
+        comments.extend(segment.citation.lines(&segment.name));
+    }
+    comments
+}
+
+/// Values of a `ROThermal_PRESET` node read from a cfg file, keys the node lacks are `None`
+#[derive(Debug, Clone, Default)]
+pub struct PresetData {
+    pub name: String,
+    pub description: String,
+    /// file the preset was read from
+    pub path: PathBuf,
+    pub temp: Option<f32>,
+    pub emissive_const: Option<f32>,
+    pub absorbation_const: Option<f32>,
+    pub tickness_min: Option<f32>,
+    pub areal_density_min: Option<f32>,
+    pub cp_min: Option<f32>,
+    pub insulance_min: Option<f32>,
+    pub tickness_max: Option<f32>,
+    pub areal_density_max: Option<f32>,
+    pub cp_max: Option<f32>,
+    pub insulance_max: Option<f32>,
+    /// `ModuleAblator` is on, the opposite of `disableModAblator`
+    pub has_ablator: Option<bool>,
+    pub cost_per_area: Option<f32>,
+    pub cost_per_area_max: Option<f32>,
+}
+
+impl PresetData {
+    /// Read the values `Preset::write` writes from `node`, `path` is the cfg file
+    pub fn from_node(node: &ConfigNode, path: &Path) -> Result<Self, CalcError> {
+        let value = |key: &str| -> Result<Option<f32>, CalcError> {
+            match node.get(key) {
+                Some(value) => value.parse::<f32>()
+                    .map(Some)
+                    .map_err(|_err| CalcError::parse(path, node.line, key, value)),
+                None => Ok(None),
+            }
+        };
+        let temp = value("skinMaxTemp")?;
+        // curves are read at skinMaxTemp
+        let number = |key: &str| -> Result<Option<f32>, CalcError> {
+            let curve = node.nodes.iter().find(|curve| !curve.name.starts_with('!') && curve.type_name() == key);
+            match (value(key)?, curve, temp) {
+                (None, Some(curve), Some(temp)) => curve_at(curve, temp, path).map(Some),
+                (value, _, _)                   => Ok(value),
+            }
+        };
+        // patches name their preset in the filter, @ROThermal_PRESET[HRSI]
+        let filter = node.name.split_once('[')
+            .and_then(|(_operator, rest)| rest.split_once(']'))
+            .map(|(filter, _rest)| filter);
+        let Some(name) = node.get("name").or(filter) else {
+            return Err(CalcError::config(path, node.line, &format!("{} lacks \"name\"", node.type_name())));
+        };
+
+        Ok(PresetData {
+            name: name.to_string(),
+            description: node.get("description").unwrap_or_default().to_string(),
+            path: path.to_path_buf(),
+            temp,
+            emissive_const: number("emissiveConstant")?,
+            absorbation_const: number("absorptiveConstant")?,
+            tickness_min: number("skinHeightMin")?,
+            areal_density_min: number("skinMassPerArea")?,
+            cp_min: number("skinSpecificHeatCapacity")?,
+            insulance_min: number("thermalInsulance")?,
+            tickness_max: number("skinHeightMax")?,
+            areal_density_max: number("skinMassPerAreaMax")?,
+            cp_max: number("skinSpecificHeatCapacityMax")?,
+            insulance_max: number("thermalInsulanceMax")?,
+            has_ablator: node.get("disableModAblator")
+                .map(|value| value.parse::<bool>().map(|disabled| !disabled).map_err(|_err| CalcError::parse(path, node.line, "disableModAblator", value)))
+                .transpose()?,
+            cost_per_area: number("costPerArea")?,
+            cost_per_area_max: number("costPerAreaMax")?,
+        })
+    }
+}
+
+/// Value of a FloatCurve node at `temp`, linear between its keys
+fn curve_at(curve: &ConfigNode, temp: f32, path: &Path) -> Result<f32, CalcError> {
+    let mut keys = Vec::<(f32, f32)>::new();
+    for (key, value) in curve.values.iter().filter(|(key, _value)| key == "key") {
+        let mut numbers = value.split_whitespace().map(|number| number.parse::<f32>());
+        match (numbers.next(), numbers.next()) {
+            (Some(Ok(temp)), Some(Ok(value))) => keys.push((temp, value)),
+            _                                 => return Err(CalcError::parse(path, curve.line, key, value)),
+        }
+    }
+    keys.into_iter().collect::<PropertyTable<f32>>().at(temp)
+}
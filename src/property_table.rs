@@ -0,0 +1,170 @@
+//! Temperature dependent values, piecewise linear between their rows.
+
+use super::error::CalcError;
+use super::interpolation::{lerp, locate, Lerp, Position};
+
+use std::ops::{Add, Mul, Sub};
+
+/// What [`PropertyTable::at`] returns outside of the rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// hold the first & last row
+    #[default]
+    Clamp,
+    /// continue the first & last segment
+    Linear,
+    /// fail with [`CalcError::OutOfRange`]
+    Error,
+}
+
+/// Rows of values sorted by temperature
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyTable<T> {
+    temps: Vec<f32>,
+    values: Vec<T>,
+    pub extrapolation: Extrapolation,
+}
+
+impl<T> Default for PropertyTable<T> {
+    fn default() -> Self {
+        PropertyTable { temps: Vec::new(), values: Vec::new(), extrapolation: Extrapolation::default() }
+    }
+}
+
+impl<T> FromIterator<(f32, T)> for PropertyTable<T> {
+    /// Rows get sorted by temperature, rows of equal temperature keep their order
+    fn from_iter<I: IntoIterator<Item = (f32, T)>>(rows: I) -> Self {
+        let mut rows = rows.into_iter().collect::<Vec<(f32, T)>>();
+        rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let (temps, values) = rows.into_iter().unzip();
+        PropertyTable { temps, values, extrapolation: Extrapolation::default() }
+    }
+}
+
+impl<T> PropertyTable<T> {
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.temps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.temps.is_empty()
+    }
+
+    pub fn temps(&self) -> &[f32] {
+        &self.temps
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Values can be changed in place, the temperatures can't
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &T)> {
+        self.temps.iter().copied().zip(self.values.iter())
+    }
+
+    /// Same temperatures, values from `f(temp, value)`
+    pub fn map<U, F: FnMut(f32, &T) -> U>(&self, mut f: F) -> PropertyTable<U> {
+        PropertyTable {
+            temps: self.temps.clone(),
+            values: self.iter().map(|(temp, value)| f(temp, value)).collect(),
+            extrapolation: self.extrapolation,
+        }
+    }
+
+    /// Where `temp` lies between the rows
+    pub fn position(&self, temp: f32) -> Position {
+        locate(&self.temps, temp)
+    }
+}
+
+impl<T: Lerp> PropertyTable<T> {
+    /// Value at `temp`, outside of the rows as `extrapolation` says
+    pub fn at(&self, temp: f32) -> Result<T, CalcError> {
+        let (Some(&first), Some(&last)) = (self.temps.first(), self.temps.last()) else {
+            return Err(CalcError::EmptyTable);
+        };
+        let n = self.len();
+
+        match (self.position(temp), self.extrapolation) {
+            (Position::At(i), _) => Ok(self.values[i]),
+            (Position::Between(i), _) => Ok(self.segment(i, i + 1, temp)),
+            (_, Extrapolation::Error) => Err(CalcError::OutOfRange { temp, min: first, max: last }),
+            (Position::Below, Extrapolation::Linear) if n > 1 => Ok(self.segment(0, 1, temp)),
+            (Position::Above, Extrapolation::Linear) if n > 1 => Ok(self.segment(n - 2, n - 1, temp)),
+            (Position::Below, _) => Ok(self.values[0]),
+            (Position::Above, _) => Ok(self.values[n - 1]),
+        }
+    }
+
+    /// Values at the temperatures of `grid`
+    pub fn resample(&self, grid: &[f32]) -> Result<Self, CalcError> {
+        let values = grid.iter().map(|temp| self.at(*temp)).collect::<Result<Vec<T>, CalcError>>()?;
+        Ok(PropertyTable { temps: grid.to_vec(), values, extrapolation: self.extrapolation })
+    }
+
+    /// ∫ f(value) dT from `from` to `to`, exact for the piecewise linear curve of `f` between the rows
+    pub fn integrate<F: Fn(&T) -> f32>(&self, from: f32, to: f32, f: F) -> Result<f32, CalcError> {
+        if to < from {
+            return Ok(-self.integrate(to, from, f)?);
+        }
+        // rows inside the bounds are the corners of the curve
+        let mut temps = vec![from];
+        temps.extend(self.temps.iter().copied().filter(|temp| *temp > from && *temp < to));
+        temps.push(to);
+
+        let mut sum = 0.0;
+        let mut previous = f(&self.at(from)?);
+        for pair in temps.windows(2) {
+            let value = f(&self.at(pair[1])?);
+            sum += (previous + value) / 2.0 * (pair[1] - pair[0]);
+            previous = value;
+        }
+        Ok(sum)
+    }
+
+    fn segment(&self, lower: usize, upper: usize, temp: f32) -> T {
+        lerp(self.temps[lower], self.values[lower], self.temps[upper], self.values[upper], temp)
+    }
+
+    /// Combine with `other` row by row, `other` is taken at the temperatures of `self`
+    fn combine<F: Fn(T, T) -> T>(&self, other: &Self, f: F) -> Result<Self, CalcError> {
+        let other = if other.temps == self.temps { other.clone() } else { other.resample(&self.temps)? };
+        Ok(PropertyTable {
+            temps: self.temps.clone(),
+            values: self.values.iter().zip(other.values.iter()).map(|(a, b)| f(*a, *b)).collect(),
+            extrapolation: self.extrapolation,
+        })
+    }
+}
+
+impl<T: Lerp> Add for &PropertyTable<T> {
+    type Output = Result<PropertyTable<T>, CalcError>;
+    fn add(self, other: Self) -> Self::Output {
+        self.combine(other, |a, b| a + b)
+    }
+}
+
+impl<T: Lerp> Sub for &PropertyTable<T> {
+    type Output = Result<PropertyTable<T>, CalcError>;
+    fn sub(self, other: Self) -> Self::Output {
+        self.combine(other, |a, b| a - b)
+    }
+}
+
+impl<T: Lerp> Mul<f32> for &PropertyTable<T> {
+    type Output = PropertyTable<T>;
+    fn mul(self, factor: f32) -> Self::Output {
+        self.map(|_temp, value| *value * factor)
+    }
+}
@@ -1,584 +1,758 @@
-use super::data_holder::*;
-
-use csv::StringRecord;
-use std::{
-    fs,
-    ffi::OsString,
-    error::Error,
-    process,
-    path::PathBuf,
-    fs::File,
-    io::Write,
-};
-
-
-pub fn get_files (path: String, extension: OsString) -> Vec<PathBuf> {
-    let paths = fs::read_dir(path)
-    .unwrap()
-    .filter_map(|e| e.ok())
-    .map(|e| e.path())
-    .filter(|path| path.extension().map_or(false, |ext| ext == extension))
-    .collect::<Vec<PathBuf>>();
-
-    paths
-}
-
-pub fn read_temp_list_csv2(file_path: &PathBuf) -> Vec<f32> {
-    let mut temp_list = Vec::<f32>::new();
-
-    let mut rdr = 
-    match csv::Reader::from_path(file_path) {
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading {:?} {}", &file_path, err);
-                            process::exit(1);}
-    };
-    
-    for result in rdr.records() {
-        let record = match result{
-            Ok(result) => {result},
-            Err(err) =>  {println!("Error while handling Results.csv {}", err);
-                            process::exit(1);}
-        };
-
-        match record[0].parse::<f32>() {
-            Ok(result) => {temp_list.push(result)},
-            Err(err) =>  {println!("Error while processing Results.csv Temperatures {}", err);
-                            process::exit(1);}
-        };
-    }
-    temp_list
-}
-
-pub fn read_part_csv(file_path: &PathBuf) -> (Part, Vec<(String, f32, f32, f32)> ) {
-    let mut part = Part {
-        name: "".to_string(),
-        description: "".to_string(),
-        temp: 0.0,
-        absorbation_const: 0.0,
-        cost_per_area: 0.0,
-        has_ablator: false,
-        height_min: 0.0,
-        height_max: 0.0,
-        areal_density_min: 0.0,
-        areal_density_max: 0.0,
-        tps_list: Vec::<(TPS, f32, Vec<DataTriplet>, Vec<DataTriplet>)>::new(),
-        data_min: Vec::<DataPair>::new(),
-        data_max: Vec::<DataPair>::new(),
-    };
-
-    let mut rdr = 
-    match csv::ReaderBuilder::new().has_headers(false).from_path(&file_path) {
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading file {}\n,{}", &file_path.display(), err);
-                            process::exit(1);}
-    };
-    let mut parts = Vec::<(String, f32, f32, f32)>::new();
-
-    for result in rdr.records() {
-        let record = match result {
-            Ok(result) => {result},
-            Err(err) =>  {println!("Error while reading Structure.csv {}, {}", file_path.display() , err);
-                            process::exit(1);}
-        };
-        match &record[0]{
-            "Name" =>           part.name = record[1].to_string(),
-            "Description" =>    part.description = record[1].to_string(),
-            "Temperature" => {  part.temp = match record[1].parse::<f32>() {
-                                    Ok(result) =>  result,
-                                    Err(err) => {println!("{} Error while parsing Temperature to float", err);
-                                                                process::exit(1);},
-                                };
-                            }
-            "AbsorbationConstant" => {  part.absorbation_const = match record[1].parse::<f32>() {
-                                            Ok(result) =>  result,
-                                            Err(err) => {println!("{} Error while parsing AbsorbationConstant to float", err);
-                                                                        process::exit(1);},
-                                        };
-                                    }
-            "CostPerArea" => {  part.cost_per_area = match record[1].parse::<f32>() {
-                                        Ok(result) =>  result,
-                                        Err(err) => {println!("{} Error while parsing CostPerArea to float", err);
-                                                                    process::exit(1);},
-                                    };
-                                }
-            "HasAblator" => {  part.has_ablator = match record[1].parse::<bool>() {
-                                    Ok(result) =>  result,
-                                    Err(err) => {println!("{} Error while parsing HasAblator to bool", err);
-                                                                process::exit(1);},
-                                };
-                            }
-            "Structure" =>  {
-                                let name = record[1].to_string(); 
-                                let portion =  match record[2].parse::<f32>() {
-                                        Ok(result) =>  result,
-                                        Err(err) => {println!("{} Error while parsing Portion to float", err);
-                                                                    process::exit(1);},
-                                };
-                                let height_min =  match &record[3] {
-                                    "max" => f32::INFINITY,
-                                    "min" => f32::NEG_INFINITY,
-                                    &_ =>   match record[3].parse::<f32>() {
-                                                Ok(result) =>  result,
-                                                Err(err) => {println!("{} Error while parsing Height Min to float", err);
-                                                                            process::exit(1);},
-                                            },
-                                };
-                                let height_max =  match &record[4] {
-                                    "max" => f32::INFINITY,
-                                    "min" => f32::NEG_INFINITY,
-                                    &_ =>   match record[4].parse::<f32>() {
-                                                Ok(result) =>  result,
-                                                Err(err) => {println!("{} Error while parsing Height Max to float", err);
-                                                                            process::exit(1);},
-                                            },
-                                };
-                                parts.push((name, portion, height_min, height_max))
-                            }
-            &_ => {}
-        }
-    }
-    (part, parts)
-}
-
-
-pub fn read_tps_csv(file_path: &PathBuf) -> TPS {
-    let mut read_max = false;
-    let mut layers_min = Vec::<Segment>::new();
-    let mut layers_max = Vec::<Segment>::new();
-    let mut structure = TPS {..Default::default()};
-    let mut rdr = 
-    match csv::ReaderBuilder::new().has_headers(false).from_path(&file_path) {
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading file {}\n,{}", &file_path.display(), err);
-                            process::exit(1);}
-    };
-
-    for result in rdr.records() {
-        let record = match result {
-            Ok(result) => {result},
-            Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-        };
-        match &record[0] {
-            "Name" => structure.name = record[1].to_string(),
-            "Description" => structure.description = record[1].to_string(),
-            "Temperature" => { structure.temp = match record[1].parse::<f32>() {
-                                        Ok(result) =>  result,
-                                        Err(err) => {println!("{} Error while parsing Temperature to float", err);
-                                            process::exit(1);},
-                                    };
-                             }
-            "Min" =>        read_max = false,
-            "Max" =>        read_max = true,
-            "Top Layer" =>  {   if read_max {
-                                    structure.segments_max.push(read_segment(&record));
-                                } else {
-                                    structure.segments_min.push(read_segment(&record));
-                                }
-                            },
-            "Layer" =>      {  if read_max {
-                                    layers_max.push(read_segment(&record));
-                                } else {
-                                    layers_min.push(read_segment(&record));
-                                }
-
-                            },
-            &_ => {}
-        }
-    }
-    if structure.name == "" {
-        {println!("Error Structure file lacks \"Name\" entry"); process::exit(1);}
-    }
-    if structure.temp == 0.0 {
-        {println!("Error Structure file lacks \"Temperature\" entry"); process::exit(1);}
-    }
-    structure.segments_min.append(&mut layers_min);
-    if !layers_max.is_empty() {
-        structure.segments_max.append(&mut layers_max);
-    } 
-    structure
-}
-
-fn read_segment(record: &StringRecord) -> Segment {
-    let mut segment = Segment{..Default::default()};
-    segment.path = record[1].to_string();
-    segment.portion = match record[2].parse::<f32>() {
-            Ok(result) =>  result,
-            Err(err) => {println!("{} Error while parsing Top Portion to float", err);
-                process::exit(1);},
-        };
-    segment.tickness = match record[3].parse::<f32>() {
-            Ok(result) =>  result,
-            Err(err) => {println!("{} Error while parsing Top Tickness to float", err);
-                process::exit(1);},
-        };
-    segment.temp_hot_side = match record[4].parse::<f32>() {
-            Ok(result) =>  result,
-            Err(err) => {println!("{} Error while parsing Temp Hot Side to float", err);
-                process::exit(1);},
-        };
-    segment.temp_cold_side = match record[5].parse::<f32>() {
-            Ok(result) =>  result,
-            Err(err) => {println!("{} Error while parsing Temp Cold Side to float", err);
-                process::exit(1);},
-        };
-    segment
-}
-
-pub fn read_material_csv(segment: &mut Segment) -> Result<(), Box<dyn Error>> {
-    let mut rdr = match csv::ReaderBuilder::new().has_headers(false).from_path(&segment.path) {
-                            Ok(result) => {result},
-                            Err(_err) =>  {println!("Error opening & reading file {} ", &segment.path);
-                                                    process::exit(1);},
-    };
-    let mut found_temperature: bool = false;
-
-    for result in rdr.records() {
-        let record = result?;
-
-        if found_temperature == true {
-            let temp;
-            let cp;
-            let k; 
-            let e; 
-            match record[0].parse::<f32>() {
-                Ok(result) => {temp = result},
-                Err(_err) =>  temp = 0.0,
-            };
-            match record[1].parse::<f32>() {
-                Ok(result) => {cp = result},
-                Err(_err) =>  cp = 0.0,
-            };
-            match record[2].parse::<f32>() {
-                Ok(result) => {k = result},
-                Err(_err) =>  k = 0.0,
-            };
-            match record[3].parse::<f32>() {
-                Ok(result) => {e = result},
-                Err(_err) =>  e = 0.0,
-            };
-            segment.data_csv.push(DataPair{0: temp, 1: Data{cp, R_th: k, e: e}});
-        } else {
-            match &record[0]{
-                "Name"              => segment.name = record[1].parse().unwrap(),
-                "Temperature Limit" => match record[1].parse::<f32>() {
-                                            Ok(result) => {segment.temp_max = result;},
-                                            Err(_err) =>  {println!("{} Can not convert Temperature Limit into float", segment.name);
-                                                                            process::exit(1);},
-                                        },
-                "Density"           => {    match record[1].parse::<f32>() {
-                                                Ok(result) => {segment.density = result;},
-                                                Err(_err) =>  {println!("{} Can not convert Density into float", segment.name);
-                                                                                process::exit(1);},
-                                            };
-                                        },
-                "Additive Areal Weight" => {match record[1].parse::<f32>() {
-                                                    Ok(result) => {segment.additive_areal_weight = result;},
-                                                    Err(_err) =>  {println!("{} Can not convert Additive Areal Weight into float", segment.name);
-                                                                                    process::exit(1);},
-                                                };
-                                            },
-                "Temperature"       => found_temperature = true,
-                &_                  => {},
-            }
-        }
-    }
-    //segment.areal_density = segment.areal_density * segment.tickness + segment.additive_areal_weight;
-    Ok(())
-}
-
-pub fn output_layer(layer: &Segment, path: &String) -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all(&path)?;
-    let output_file = path.clone() + "/" + &layer.name + "_csv_data.csv";
-
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Layer", "Heat Capacity", "Thermal Insulance", "Emissivity",])?;
-    
-    //    thermal_prop_layer_temp
-    //    thermal_prop_struct_temp
-    //    thermal_prop_struct_temp_frac
-    for data in layer.data_csv.iter() {
-        wtr.serialize((data.0, data.1.cp, data.1.R_th, data.1.e ))?;
-    }
-    wtr.flush()?;
-
-
-    let output_file = path.clone() + "/" + &layer.name + "_avg_r.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity", "Temp Layer"])?;
-    
-    //    thermal_prop_layer_temp
-    //    thermal_prop_struct_temp
-    //    thermal_prop_struct_temp_frac
-    for  data in layer.data_avg_r.iter() {
-        wtr.serialize((data.temp_part, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
-    }
-    wtr.flush()?;
-
-
-    let output_file = path.clone() + "/" + &layer.name + "_height_adjusted.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity", "Temp Layer"])?;
-    
-    //    thermal_prop_layer_temp
-    //    thermal_prop_struct_temp
-    //    thermal_prop_struct_temp_frac
-    for  data in layer.data_height_adjust.iter() {
-        wtr.serialize((data.temp_part, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
-    }
-    wtr.flush()?;
-
-
-    let output_file = path.clone() + "/" + &layer.name + "_data_tps_temp_map.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity","Temp Layer"])?;
-    
-    //    thermal_prop_layer_temp
-    //    thermal_prop_struct_temp
-    //    thermal_prop_struct_temp_frac
-    for data in layer.data_tps_temp_map.iter() {
-        wtr.serialize((data.temp_part, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
-    }
-    wtr.flush()?;
-
-    let output_file = path.clone() + "/" + &layer.name + "_tps_temp_mult.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity","Temp Layer"])?;
-    
-    //    thermal_prop_layer_temp
-    //    thermal_prop_struct_temp
-    //    thermal_prop_struct_temp_frac
-    for data in layer.data_tps_temp_mult.iter() {
-        wtr.serialize((data.temp_part, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
-    }
-    wtr.flush()?;
-
-
-    Ok(())
-}
-
-pub fn output_tps(tps: &TPS, path: String) -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all(&path)?;
-    fs::create_dir_all(path.to_owned() + "csv")?;
-    let mut index = 0;
-    // write structure into path
-    let output_file = path.clone() + "csv/" + &tps.name + "_min.csv";
-
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity"])?;
-    
-    for (i, data) in tps.data_min.iter().enumerate() {
-        wtr.serialize((data.0, data.1.cp, 1.0 / data.1.R_th, data.1.e))?;
-        if data.0 >= tps.temp - 25.0 && data.0 <= tps.temp + 25.0 {
-            index = i;
-        }
-    }
-    wtr.flush()?;
-
-
-    let output_file = path.clone() + "csv/" + &tps.name + "_max.csv";
-
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity"])?;
-    
-    for (i, data) in tps.data_max.iter().enumerate() {
-        wtr.serialize((data.0, data.1.cp, 1.0 / data.1.R_th, data.1.e))?;
-        if data.0 >= tps.temp - 25.0 && data.0 <= tps.temp + 25.0 {
-            index = i;
-        }
-    }
-    wtr.flush()?;
-
-    // write layer into strucure Folder
-    let directory = (&path).to_string() + "Debug_Info/" + &tps.name;
-    fs::create_dir_all(&directory)?;
-    for layer in tps.segments_min.clone() {
-        output_layer(&layer,&directory)?;
-    }
-
-    let directory = path.clone() + "TPS/";
-    let output_file = directory.to_owned() + &tps.name + ".cfg";
-    fs::create_dir_all(&directory)?;
-    let mut file = File::create(output_file)?;
-
-    writeln!(file, "ROThermal_PRESET\n{{")?;
-    writeln!(file, "    name = {}" , tps.name)?;
-    writeln!(file, "    description = {}" , tps.description)?;
-    writeln!(file, "    type = Skin\n")?;
-
-    writeln!(file, "    skinMaxTemp = {}" , tps.temp)?;
-    writeln!(file, "    emissiveConstant = {}" , tps.data_min[index].1.e)?;
-    writeln!(file, "    absorptiveConstant = {}\n" , tps.absorbation_const)?;
-
-    writeln!(file, "    skinHeightMin = {:0.4}" , tps.tickness_min)?;
-    writeln!(file, "    skinMassPerArea = {}" , tps.areal_density_min)?;
-    writeln!(file, "    skinSpecificHeatCapacity = {}" , tps.data_min[index].1.cp)?;
-    writeln!(file, "    thermalInsulance = {}\n" , tps.data_min[index].1.R_th)?;
-
-    writeln!(file, "    skinHeightMax = {:0.4}" , tps.tickness_max)?;
-    writeln!(file, "    skinMassPerAreaMax = {}" , tps.areal_density_max)?;
-    writeln!(file, "    skinSpecificHeatCapacityMax = {}" , tps.data_max[index].1.cp)?;
-    writeln!(file, "    thermalInsulanceMax = {}\n" , tps.data_max[index].1.R_th)?;
-
-    writeln!(file, "    disableModAblator = {}" , tps.has_ablator)?;
-    writeln!(file, "    costPerArea = {}" , tps.cost_per_area)?;
-    writeln!(file, "}}")?;
-
-    writeln!(file, "// Min: \n// Segment, Height")?;
-    for segment in &tps.segments_min {
-        writeln!(file, "// {}, {}",&segment.name, segment.tickness)?;    
-    }
-    writeln!(file, "\n// Max: \n// Segment, Height")?;
-    for segment in &tps.segments_max {
-        writeln!(file, "// {}, {}",&segment.name, segment.tickness)?;    
-    }
-
-    Ok(())
-}
-
-pub fn output_part(part: Part, path: String) -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all(&path)?;
-    fs::create_dir_all(path.to_owned() + "csv/")?;
-    let mut index = 0;
-
-    let output_file = path.clone() + "csv/" + &part.name + "_min.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while writing {}: {}", path.clone() + "csv/" + &part.name + "_min.csv", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"])?;
-    
-    for (i, data) in part.data_min.iter().enumerate() {
-        wtr.serialize((data.0, data.1.cp, data.1.R_th, data.1.e))?;
-        if data.0 >= part.temp - 25.0 && data.0 <= part.temp + 25.0 {
-            index = i;
-        }
-    }
-    wtr.flush()?;
-
-
-    let output_file = path.to_owned()+ "csv/" + &part.name + "_max.csv";
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while writing {}: {}", path.to_owned() + "csv/" + &part.name + "_max.csv", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"])?;
-    
-    for data in part.data_max.iter() {
-        wtr.serialize((data.0, data.1.cp, data.1.R_th, data.1.e))?;
-    }
-    wtr.flush()?;
-
-    let directory = path.to_owned()+ "Part/";
-    let output_file = directory.to_owned() + &part.name + ".cfg";
-    fs::create_dir_all(&directory)?;
-    let mut file = File::create(output_file)?;
-    
-    writeln!(file, "ROThermal_PRESET\n{{")?;
-    writeln!(file, "    name = {}" , part.name)?;
-    writeln!(file, "    description = {}" , part.description)?;
-    writeln!(file, "    type = Skin\n")?;
-
-    writeln!(file, "    skinMaxTemp = {}" , part.temp)?;
-    writeln!(file, "    emissiveConstant = {}" , part.data_min[index].1.e)?;
-    writeln!(file, "    absorptiveConstant = {}\n" , part.absorbation_const)?;
-
-    writeln!(file, "    skinHeightMin = {:0.4}" , part.height_min)?;
-    writeln!(file, "    skinMassPerArea = {}" , part.areal_density_min)?;
-    writeln!(file, "    skinSpecificHeatCapacity = {}" , part.data_min[index].1.cp)?;
-    writeln!(file, "    thermalInsulance = {}\n" , f32::powf(part.data_min[index].1.R_th, -1.0))?;
-
-    writeln!(file, "    skinHeightMax = {:0.4}" , part.height_max)?;
-    writeln!(file, "    skinMassPerAreaMax = {}" , part.areal_density_max)?;
-    writeln!(file, "    skinSpecificHeatCapacityMax = {}" , part.data_max[index].1.cp)?;
-    writeln!(file, "    thermalInsulanceMax = {}\n" , f32::powf(part.data_max[index].1.R_th, -1.0))?;
-
-    writeln!(file, "    disableModAblator = {}" , part.has_ablator)?;
-    writeln!(file, "    costPerArea = {}" , part.cost_per_area)?;
-    writeln!(file, "}}")?;
-
-    writeln!(file, "// Segment, Portion, Min Height, Max Height")?;
-    for structure in &part.tps_list {
-        writeln!(file, "// {}, {}, {}, {}",&structure.0.name, structure.1, &structure.0.tickness_min, &structure.0.tickness_max)?;    
-    }
-
-    Ok(())
-}
-
-pub fn output_data_triplet(name: &String, data: &Vec<DataTriplet>, path: String) -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all(&path)?;
-
-    let output_file = path.clone() + "/" + &name + ".csv";
-
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity", "Temp Layer"])?;
-    
-    for data in data.iter() {
-        wtr.serialize((data.temp_part, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
-    }
-    wtr.flush()?;
-    Ok(())
-}
-
-pub fn output_data_pair(name: &String, data: &Vec<DataPair>, path: String) -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all(&path)?;
-
-    let output_file = path.clone() + "/" + &name + ".csv";
-
-    let mut wtr = match csv::Writer::from_path(&output_file){
-        Ok(result) => {result},
-        Err(err) =>  {println!("Error while reading Results.csv {}", err);
-                            process::exit(1);}
-    };
-
-    wtr.write_record(&["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"])?;
-    
-    for data in data {
-        wtr.serialize((data.0, data.1.cp, data.1.R_th, data.1.e))?;
-    }
-    wtr.flush()?;
-    Ok(())
-}
+use super::data_holder::*;
+use super::error::CalcError;
+use super::info::{IndexEntry, Info};
+use super::interpolation::{Interpolation, Scheme};
+use super::material_library::MaterialLibrary;
+use super::ablator::Ablator;
+use super::config_node::ConfigNode;
+use super::preset::{Preset, PresetData, PresetFormat};
+use super::property_table::PropertyTable;
+use super::steady::{apply_steady_profile, BackBoundary};
+use super::transient::Transient;
+use super::units::*;
+use super::validate::{Issue, LayerMargin, Severity};
+
+use csv::StringRecord;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    fs::File,
+    io::{self, Write},
+};
+
+
+/// Files in `path` with `extension`, sorted so runs are reproducible
+pub fn get_files (path: &Path, extension: &str) -> Result<Vec<PathBuf>, CalcError> {
+    let mut paths = fs::read_dir(path)
+    .map_err(|err| CalcError::io(path, err))?
+    .filter_map(|e| e.ok())
+    .map(|e| e.path())
+    .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+    .collect::<Vec<PathBuf>>();
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Csv file held in memory, so errors can name the right line.
+/// With "\r\n" endings the csv crate miscounts lines and reports the record start at the "\n".
+struct CsvSource<'a> {
+    path: &'a Path,
+    content: String,
+}
+impl<'a> CsvSource<'a> {
+    fn open(path: &'a Path) -> Result<Self, CalcError> {
+        let content = fs::read_to_string(path).map_err(|err| CalcError::io(path, err))?;
+        Ok(CsvSource { path, content })
+    }
+
+    fn records(&self, has_headers: bool) -> impl Iterator<Item = Result<StringRecord, CalcError>> + '_ {
+        self.records_with(csv::ReaderBuilder::new().has_headers(has_headers))
+    }
+
+    /// Records of material files, their header rows may have more cells than the table
+    fn material_records(&self) -> impl Iterator<Item = Result<StringRecord, CalcError>> + '_ {
+        self.records_with(csv::ReaderBuilder::new().has_headers(false).flexible(true))
+    }
+
+    fn records_with(&self, builder: &csv::ReaderBuilder) -> impl Iterator<Item = Result<StringRecord, CalcError>> + '_ {
+        builder.from_reader(self.content.as_bytes())
+            .into_records()
+            .map(|result| result.map_err(|err| CalcError::csv(self.path, err)))
+    }
+
+    fn line(&self, record: &StringRecord) -> u64 {
+        let byte = record.position().map_or(0, |pos| pos.byte() as usize);
+        self.content.as_bytes().iter().take(byte + 1).filter(|c| **c == b'\n').count() as u64 + 1
+    }
+
+    fn parse_error(&self, record: &StringRecord, column: &str, value: &str) -> CalcError {
+        CalcError::parse(self.path, self.line(record), column, value)
+    }
+
+    /// Parse column `index` of `record`, `column` names it in the error
+    fn parse_f32(&self, record: &StringRecord, index: usize, column: &str) -> Result<f32, CalcError> {
+        let value = record.get(index).unwrap_or("");
+        value.trim().parse::<f32>().map_err(|_err| self.parse_error(record, column, value))
+    }
+
+    /// Like [`parse_f32`](Self::parse_f32), an empty cell is `None`
+    fn parse_optional_f32(&self, record: &StringRecord, index: usize, column: &str) -> Result<Option<f32>, CalcError> {
+        if record.get(index).unwrap_or("").trim().is_empty() {
+            return Ok(None);
+        }
+        self.parse_f32(record, index, column).map(Some)
+    }
+
+    /// Like [`parse_f32`](Self::parse_f32), an empty cell is 0.0
+    fn parse_f32_or_empty(&self, record: &StringRecord, index: usize, column: &str) -> Result<f32, CalcError> {
+        Ok(self.parse_optional_f32(record, index, column)?.unwrap_or(0.0))
+    }
+
+    /// Specific Heat, Thermal Conductivity & Emissivity in columns 1 to 3, empty cells are 0.0
+    fn parse_data(&self, record: &StringRecord) -> Result<Data, CalcError> {
+        Ok(Data {
+            cp: self.parse_f32_or_empty(record, 1, "Specific Heat")?,
+            R_th: self.parse_f32_or_empty(record, 2, "Thermal Conductivity")?,
+            e: self.parse_f32_or_empty(record, 3, "Emissivity")?,
+        })
+    }
+
+    /// "Back Face" row of a TPS: `Temperature,<K>`, `Radiation,<K>,<emissivity>` or `Convection,<K>,<W/m²K>`
+    fn parse_back_boundary(&self, record: &StringRecord) -> Result<BackBoundary, CalcError> {
+        let mode = record.get(1).unwrap_or("");
+        let temp = self.parse_f32(record, 2, "Back Face temperature")?;
+        match mode.trim().to_lowercase().as_str() {
+            "temperature" => Ok(BackBoundary::Temperature(temp)),
+            "radiation"   => Ok(BackBoundary::Radiation { temp, emissivity: self.parse_f32(record, 3, "Back Face emissivity")? }),
+            "convection"  => Ok(BackBoundary::Convection { temp, coefficient: self.parse_f32(record, 3, "Back Face coefficient")? }),
+            _             => Err(self.parse_error(record, "Back Face", mode)),
+        }
+    }
+
+    /// "Cost" row of a material, `Cost,<value>,<basis>` with the basis "/kg" or "/m²·mm"
+    fn parse_cost(&self, record: &StringRecord) -> Result<Cost, CalcError> {
+        let value = self.parse_f32(record, 1, "Cost")?;
+        let basis = record.get(2).unwrap_or("");
+        let basis = CostBasis::parse(basis).ok_or_else(|| self.parse_error(record, "Cost basis", basis))?;
+        Ok(Cost { value, basis })
+    }
+
+    /// "Interpolation" row of a material: one scheme for all columns or one per column,
+    /// empty cells of the latter stay linear
+    fn parse_interpolation(&self, record: &StringRecord) -> Result<Interpolation, CalcError> {
+        let mut schemes = [Scheme::Linear; 3];
+        for (i, scheme) in schemes.iter_mut().enumerate() {
+            let value = record.get(i + 1).unwrap_or("");
+            if !value.trim().is_empty() {
+                *scheme = Scheme::parse(value).ok_or_else(|| self.parse_error(record, "Interpolation", value))?;
+            }
+        }
+        let is_single = (2..4).all(|i| record.get(i).unwrap_or("").trim().is_empty());
+        let [specific_heat, conductivity, emissivity] = schemes;
+        if is_single {
+            Ok(Interpolation::all(specific_heat))
+        } else {
+            Ok(Interpolation { specific_heat, conductivity, emissivity })
+        }
+    }
+
+    /// "Units" row of a material, one unit per table column
+    fn parse_units(&self, record: &StringRecord) -> Result<Units, CalcError> {
+        let cell = |i: usize| record.get(i).unwrap_or("");
+        let temperature = TemperatureUnit::parse(cell(1)).ok_or_else(|| self.parse_error(record, "Temperature unit", cell(1)))?;
+        let specific_heat = specific_heat_factor(cell(2)).ok_or_else(|| self.parse_error(record, "Specific Heat unit", cell(2)))?;
+        let conductivity = conductivity_factor(cell(3)).ok_or_else(|| self.parse_error(record, "Thermal Conductivity unit", cell(3)))?;
+        if !is_dimensionless(cell(4)) {
+            return Err(self.parse_error(record, "Emissivity unit", cell(4)));
+        }
+        Ok(Units { temperature, specific_heat, conductivity })
+    }
+
+    /// Free text of a header row, cells split at unquoted commas get joined again
+    fn text(&self, record: &StringRecord) -> String {
+        record.iter().skip(1).map(str::trim).filter(|cell| !cell.is_empty()).collect::<Vec<&str>>().join(", ")
+    }
+
+    /// Height of a part structure, "min" & "max" refer to the TPS limits
+    fn parse_height(&self, record: &StringRecord, index: usize, column: &str) -> Result<f32, CalcError> {
+        match record.get(index).unwrap_or("") {
+            "max" => Ok(f32::INFINITY),
+            "min" => Ok(f32::NEG_INFINITY),
+            &_ => self.parse_f32(record, index, column),
+        }
+    }
+}
+
+pub fn read_temp_list_csv2(file_path: &Path) -> Result<Vec<f32>, CalcError> {
+    let mut temp_list = Vec::<f32>::new();
+
+    let source = CsvSource::open(file_path)?;
+
+    for result in source.records(true) {
+        let record = result?;
+        temp_list.push(source.parse_f32(&record, 0, "Temp")?);
+    }
+    Ok(temp_list)
+}
+
+/// Curve as `output_tps` & `output_part` write it to csv/, the insulance column is kept as written
+pub fn read_curve_csv(file_path: &Path) -> Result<PropertyTable<Data>, CalcError> {
+    let mut curve = Vec::<(f32, Data)>::new();
+
+    let source = CsvSource::open(file_path)?;
+
+    for result in source.records(true) {
+        let record = result?;
+        let data = Data {
+            cp: source.parse_f32(&record, 1, "Heat Capacity")?,
+            R_th: source.parse_f32(&record, 2, "Thermal Insulance")?,
+            e: source.parse_f32(&record, 3, "Emissivity")?,
+        };
+        curve.push((source.parse_f32(&record, 0, "Temp Part")?, data));
+    }
+    Ok(curve.into_iter().collect())
+}
+
+pub fn read_part_csv(file_path: &Path) -> Result<PartSpec, CalcError> {
+    let mut part = PartSpec {..Default::default()};
+
+    let source = CsvSource::open(file_path)?;
+
+    for result in source.records(false) {
+        let record = result?;
+        match &record[0]{
+            "Name" =>           part.name = record[1].to_string(),
+            "Description" =>    part.description = record[1].to_string(),
+            "Temperature" =>    part.temp = source.parse_f32(&record, 1, "Temperature")?,
+            "AbsorbationConstant" => part.absorbation_const = source.parse_f32(&record, 1, "AbsorbationConstant")?,
+            "CostPerArea" =>    part.cost_per_area = source.parse_f32(&record, 1, "CostPerArea")?,
+            "HasAblator" => {   part.has_ablator = record[1].parse::<bool>()
+                                    .map_err(|_err| source.parse_error(&record, "HasAblator", &record[1]))?;
+                            }
+            "Structure" =>  {
+                                let name = record[1].to_string();
+                                let portion = source.parse_f32(&record, 2, "Portion")?;
+                                let height_min = source.parse_height(&record, 3, "Height Min")?;
+                                let height_max = source.parse_height(&record, 4, "Height Max")?;
+                                part.structures.push(StructureRef{name, portion, height_min, height_max})
+                            }
+            &_ => {}
+        }
+    }
+    if part.name.is_empty() {
+        return Err(CalcError::missing(file_path, "Name"));
+    }
+    part.info = find_info(file_path)?;
+    Ok(part)
+}
+
+
+pub fn read_tps_csv(file_path: &Path) -> Result<TpsSpec, CalcError> {
+    let mut read_max = false;
+    let mut layers_min = Vec::<Segment>::new();
+    let mut layers_max = Vec::<Segment>::new();
+    let mut structure = TpsSpec {path: file_path.to_path_buf(), ..Default::default()};
+    let source = CsvSource::open(file_path)?;
+
+    for result in source.records(false) {
+        let record = result?;
+        match &record[0] {
+            "Name" => structure.name = record[1].to_string(),
+            "Description" => structure.description = record[1].to_string(),
+            "Temperature" => structure.temp = source.parse_f32(&record, 1, "Temperature")?,
+            "Back Face" =>  structure.back_boundary = Some(source.parse_back_boundary(&record)?),
+            "Manufacturing Factor" => structure.manufacturing_factor = Some(source.parse_f32(&record, 1, "Manufacturing Factor")?),
+            "Min" =>        read_max = false,
+            "Max" =>        read_max = true,
+            "Top Layer" =>  {   let mut segment = read_segment(&source, &record)?;
+                                segment.top_layer = true;
+                                if read_max {
+                                    structure.segments_max.push(segment);
+                                } else {
+                                    structure.segments_min.push(segment);
+                                }
+                            },
+            "Layer" =>      {  if read_max {
+                                    layers_max.push(read_segment(&source, &record)?);
+                                } else {
+                                    layers_min.push(read_segment(&source, &record)?);
+                                }
+
+                            },
+            "" => {}
+            &_ => structure.unknown_keys.push((source.line(&record), record[0].to_string())),
+        }
+    }
+    if structure.name.is_empty() {
+        return Err(CalcError::missing(file_path, "Name"));
+    }
+    if structure.temp == 0.0 {
+        return Err(CalcError::missing(file_path, "Temperature"));
+    }
+    structure.segments_min.append(&mut layers_min);
+    if !layers_max.is_empty() {
+        structure.segments_max.append(&mut layers_max);
+    }
+    structure.info = find_info(file_path)?;
+    Ok(structure)
+}
+
+/// The `.info` sidecar belonging to the TPS or part file `csv_path`, see [`Info`].
+///
+/// Its own `<name>.info` has to parse. Other sidecars of the folder are only searched for `Applies To`,
+/// those that don't parse are skipped, [`check_info_files`] reports them
+pub fn find_info(csv_path: &Path) -> Result<Option<Info>, CalcError> {
+    let own = csv_path.with_extension("info");
+    if own.is_file() {
+        return read_info(&own).map(Some);
+    }
+    let directory = csv_path.parent().filter(|directory| !directory.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    for path in get_files(directory, "info")? {
+        let Ok(info) = read_info(&path) else { continue };
+        if info.applies_to(csv_path) {
+            return Ok(Some(info));
+        }
+    }
+    Ok(None)
+}
+
+/// Warnings for the `.info` sidecars in `directory` that don't parse & have no csv of their own,
+/// the others fail with their csv
+pub fn check_info_files(directory: &Path) -> Result<Vec<Issue>, CalcError> {
+    let issues = get_files(directory, "info")?.into_iter()
+        .filter(|path| !path.with_extension("csv").is_file())
+        .filter_map(|path| read_info(&path).err().map(|err| (path, err)))
+        .map(|(path, err)| match err {
+            CalcError::Config { line, message, .. } => Issue { severity: Severity::Warning, path, line: Some(line), message },
+            err                                     => Issue { severity: Severity::Warning, path, line: None, message: err.to_string() },
+        })
+        .collect();
+    Ok(issues)
+}
+
+pub fn read_info(file_path: &Path) -> Result<Info, CalcError> {
+    let text = fs::read_to_string(file_path).map_err(|err| CalcError::io(file_path, err))?;
+    Info::parse(file_path, &text)
+}
+
+/// Read a TPS csv together with the material data of all its layers,
+/// material references get resolved through `library`. Errors of every material file are collected.
+/// With a "Back Face" row the hot & cold sides of the layers come from [`apply_steady_profile`]
+pub fn load_tps_spec(file_path: &Path, library: &MaterialLibrary) -> Result<TpsSpec, Vec<CalcError>> {
+    let mut spec = read_tps_csv(file_path).map_err(|err| vec![err])?;
+    let mut errors = Vec::<CalcError>::new();
+
+    for segment in spec.segments_min.iter_mut().chain(spec.segments_max.iter_mut()) {
+        match library.resolve(&segment.path, file_path) {
+            Ok(path) => segment.path = path.to_string_lossy().to_string(),
+            Err(err) => {
+                errors.push(err);
+                continue;
+            },
+        }
+        if let Err(err) = read_material_csv(segment) {
+            errors.push(err);
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    if let Some(back) = spec.back_boundary {
+        for (section, segments) in [("Min", &mut spec.segments_min), ("Max", &mut spec.segments_max)] {
+            if segments.is_empty() {
+                continue;
+            }
+            if let Err(err) = apply_steady_profile(segments, spec.temp, back) {
+                let message = match err {
+                    CalcError::Simulation(message) => message,
+                    err => err.to_string(),
+                };
+                errors.push(CalcError::Simulation(format!("{}: steady profile of the {} section: {}", file_path.display(), section, message)));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(spec)
+}
+
+fn read_segment(source: &CsvSource, record: &StringRecord) -> Result<Segment, CalcError> {
+    let mut segment = Segment{..Default::default()};
+    segment.path = record.get(1).unwrap_or("").to_string();
+    segment.portion = source.parse_f32(record, 2, "Portion")?;
+    segment.tickness = source.parse_f32(record, 3, "Tickness")?;
+    // left empty when a "Back Face" row has them computed
+    segment.temp_hot_side = source.parse_f32_or_empty(record, 4, "Temp Hot Side")?;
+    segment.temp_cold_side = source.parse_f32_or_empty(record, 5, "Temp Cold Side")?;
+    Ok(segment)
+}
+
+/// All `ROThermal_PRESET` nodes of a cfg file, patches included
+pub fn read_preset_cfg(file_path: &Path) -> Result<Vec<PresetData>, CalcError> {
+    let text = fs::read_to_string(file_path).map_err(|err| CalcError::io(file_path, err))?;
+    let root = ConfigNode::parse(file_path, &text)?;
+
+    let mut nodes = Vec::<&ConfigNode>::new();
+    root.find_all("ROThermal_PRESET", &mut nodes);
+    nodes.into_iter()
+        .map(|node| PresetData::from_node(node, file_path))
+        .collect()
+}
+
+/// The "Name" entry of a material file, without reading its data
+pub fn read_material_name(file_path: &Path) -> Result<String, CalcError> {
+    let source = CsvSource::open(file_path)?;
+
+    for result in source.material_records() {
+        let record = result?;
+        match &record[0] {
+            "Name" => return Ok(record[1].to_string()),
+            "Temperature" => break,
+            &_ => {},
+        }
+    }
+    Err(CalcError::missing(file_path, "Name"))
+}
+
+/// Read a material file into `segment`, tables with a "Units" row are converted into SI
+pub fn read_material_csv(segment: &mut Segment) -> Result<(), CalcError> {
+    let file_path = PathBuf::from(&segment.path);
+    let source = CsvSource::open(&file_path)?;
+    let mut found_temperature: bool = false;
+    let mut rows = Vec::<(Option<f32>, Data)>::new();
+    let mut temp_limit = None;
+    let mut pyrolysis_temp = None;
+    let mut units = Units::default();
+    let mut ablator = Ablator::default();
+    let mut is_ablator = false;
+
+    for result in source.material_records() {
+        let record = result?;
+
+        if found_temperature {
+            rows.push((source.parse_optional_f32(&record, 0, "Temperature")?, source.parse_data(&record)?));
+        } else {
+            match &record[0]{
+                "Name"              => segment.name = record[1].to_string(),
+                "Temperature Limit" => temp_limit = Some(source.parse_f32(&record, 1, "Temperature Limit")?),
+                "Density"           => segment.density = source.parse_f32(&record, 1, "Density")?,
+                "Additive Areal Weight" => segment.additive_areal_weight = source.parse_f32(&record, 1, "Additive Areal Weight")?,
+                "Interpolation"     => segment.interpolation = source.parse_interpolation(&record)?,
+                "Units"             => units = source.parse_units(&record)?,
+                "Source"            => segment.citation.source = source.text(&record),
+                "Reference"         => segment.citation.references.push(source.text(&record)),
+                "Notes"             => segment.citation.notes.push(source.text(&record)),
+                "Confidence"        => segment.citation.confidence = source.text(&record),
+                "Char"              => ablator.char_data = source.parse_data(&record)?,
+                "Char Density"      => ablator.char_density = source.parse_f32(&record, 1, "Char Density")?,
+                "Heat of Ablation"  => {ablator.heat_of_ablation = source.parse_f32(&record, 1, "Heat of Ablation")?; is_ablator = true},
+                "Solar Absorptance" => segment.absorptance = Some(source.parse_f32(&record, 1, "Solar Absorptance")?),
+                "Cost"              => segment.cost = Some(source.parse_cost(&record)?),
+                "Pyrolysis Temperature" => {pyrolysis_temp = Some(source.parse_f32(&record, 1, "Pyrolysis Temperature")?); is_ablator = true},
+                "Temperature"       => found_temperature = true,
+                &_                  => {},
+            }
+        }
+    }
+    //segment.areal_density = segment.areal_density * segment.tickness + segment.additive_areal_weight;
+    // empty cells become 0.0, the gaps filled in later by interpolation
+    segment.temp_max = units.kelvin(temp_limit).unwrap_or(0.0);
+    segment.data_csv = rows.into_iter()
+        .map(|(temp, data)| {
+            let (temp, data) = units.convert(temp, data);
+            (temp.unwrap_or(0.0), data)
+        })
+        .collect();
+    if is_ablator {
+        ablator.pyrolysis_temp = units.kelvin(pyrolysis_temp).unwrap_or(0.0);
+        ablator.char_data = units.convert(None, ablator.char_data).1;
+        segment.ablator = Some(ablator);
+    }
+    Ok(())
+}
+
+/// Write a csv with `header`, `write_rows` serializes the content
+fn write_csv<F>(file_path: &Path, header: &[&str], write_rows: F) -> Result<(), CalcError>
+where F: FnOnce(&mut csv::Writer<File>) -> csv::Result<()> {
+    let mut wtr = csv::Writer::from_path(file_path).map_err(|err| CalcError::csv(file_path, err))?;
+
+    wtr.write_record(header).map_err(|err| CalcError::csv(file_path, err))?;
+    write_rows(&mut wtr).map_err(|err| CalcError::csv(file_path, err))?;
+    wtr.flush().map_err(|err| CalcError::io(file_path, err))
+}
+
+/// Write a text file, `write_content` fills it
+fn write_text<F>(file_path: &Path, write_content: F) -> Result<(), CalcError>
+where F: FnOnce(&mut File) -> io::Result<()> {
+    let mut file = File::create(file_path).map_err(|err| CalcError::io(file_path, err))?;
+    write_content(&mut file).map_err(|err| CalcError::io(file_path, err))
+}
+
+fn create_dir(path: &Path) -> Result<(), CalcError> {
+    fs::create_dir_all(path).map_err(|err| CalcError::io(path, err))
+}
+
+pub fn output_layer(layer: &Segment, path: &Path) -> Result<(), CalcError> {
+    create_dir(path)?;
+
+    //    thermal_prop_layer_temp
+    //    thermal_prop_struct_temp
+    //    thermal_prop_struct_temp_frac
+    let output_file = path.join(layer.name.clone() + "_csv_data.csv");
+    write_csv(&output_file, &["Temp Layer", "Heat Capacity", "Thermal Insulance", "Emissivity",], |wtr| {
+        for (temp, data) in layer.data_csv.iter() {
+            wtr.serialize((temp, data.cp, data.R_th, data.e ))?;
+        }
+        Ok(())
+    })?;
+
+    let triplets = [("_avg_r.csv", &layer.data_avg_r),
+                    ("_height_adjusted.csv", &layer.data_height_adjust),
+                    ("_data_tps_temp_map.csv", &layer.data_tps_temp_map),
+                    ("_tps_temp_mult.csv", &layer.data_tps_temp_mult)];
+    for (suffix, data) in triplets {
+        let output_file = path.join(layer.name.clone() + suffix);
+        write_csv(&output_file, &["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity", "Temp Layer"], |wtr| {
+            for (temp, data) in data.iter() {
+                wtr.serialize((temp, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Write the gap filled data of a material
+pub fn output_material(material: &Segment, path: &Path) -> Result<(), CalcError> {
+    let directory = path.join("Material");
+    create_dir(&directory)?;
+
+    let output_file = directory.join(material.name.clone() + ".csv");
+    write_csv(&output_file, &["Temperature", "Specific Heat", "Thermal Conductivity", "Emissivity"], |wtr| {
+        for (temp, data) in material.data_csv.iter() {
+            wtr.serialize((temp, data.cp, data.R_th, data.e))?;
+        }
+        Ok(())
+    })
+}
+
+pub fn output_margins(tps_name: &str, margins: &[LayerMargin], path: &Path) -> Result<(), CalcError> {
+    let directory = path.join("Margins");
+    create_dir(&directory)?;
+
+    let output_file = directory.join(tps_name.to_string() + ".csv");
+    let header = ["Section", "Layer", "Material", "Temperature Limit", "Temp Hot Side", "Temp Cold Side", "Peak Temperature", "Margin", "Cold Face Above"];
+    write_csv(&output_file, &header, |wtr| {
+        for margin in margins.iter() {
+            wtr.serialize((margin.section, margin.layer, &margin.material, margin.temp_limit,
+                           margin.temp_hot_side, margin.temp_cold_side, margin.temp_peak, margin.margin, margin.temp_above))?;
+        }
+        Ok(())
+    })
+}
+
+pub fn output_tps(tps: &TPS, path: &Path, format: &PresetFormat) -> Result<(), CalcError> {
+    create_dir(&path.join("csv"))?;
+    // write structure into path
+    let output_file = path.join("csv").join(tps.name.clone() + "_min.csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity"], |wtr| {
+        for (temp, data) in tps.data_min.iter() {
+            wtr.serialize((temp, data.cp, 1.0 / data.R_th, data.e))?;
+        }
+        Ok(())
+    })?;
+
+    let output_file = path.join("csv").join(tps.name.clone() + "_max.csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "Thermal Insulance", "Emissivity"], |wtr| {
+        for (temp, data) in tps.data_max.iter() {
+            wtr.serialize((temp, data.cp, 1.0 / data.R_th, data.e))?;
+        }
+        Ok(())
+    })?;
+
+    // write layer into strucure Folder
+    let directory = path.join("Debug_Info").join(&tps.name);
+    create_dir(&directory)?;
+    for layer in tps.segments_min.iter() {
+        output_layer(layer, &directory)?;
+    }
+
+    let directory = path.join("TPS");
+    let output_file = directory.join(tps.name.clone() + ".cfg");
+    create_dir(&directory)?;
+
+    let preset = Preset::from_tps(tps, format.reference.clone());
+    write_text(&output_file, |file| preset.write(file, format))
+}
+
+pub fn output_part(part: Part, path: &Path, format: &PresetFormat) -> Result<(), CalcError> {
+    create_dir(&path.join("csv"))?;
+
+    let output_file = path.join("csv").join(part.name.clone() + "_min.csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"], |wtr| {
+        for (temp, data) in part.data_min.iter() {
+            wtr.serialize((temp, data.cp, data.R_th, data.e))?;
+        }
+        Ok(())
+    })?;
+
+    let output_file = path.join("csv").join(part.name.clone() + "_max.csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"], |wtr| {
+        for (temp, data) in part.data_max.iter() {
+            wtr.serialize((temp, data.cp, data.R_th, data.e))?;
+        }
+        Ok(())
+    })?;
+
+    let directory = path.join("Part");
+    let output_file = directory.join(part.name.clone() + ".cfg");
+    create_dir(&directory)?;
+
+    let preset = Preset::from_part(&part, format.reference.clone());
+    write_text(&output_file, |file| preset.write(file, format))
+}
+
+/// Time history with a header row: time in s & one value per row, e.g. heat flux in W/m²
+pub fn read_history_csv(file_path: &Path) -> Result<PropertyTable<f32>, CalcError> {
+    let source = CsvSource::open(file_path)?;
+    let mut rows = Vec::<(f32, f32)>::new();
+
+    for result in source.records(true) {
+        let record = result?;
+        rows.push((source.parse_f32(&record, 0, "Time")?, source.parse_f32(&record, 1, "Value")?));
+    }
+    if rows.is_empty() {
+        return Err(CalcError::missing(file_path, "Time"));
+    }
+    Ok(rows.into_iter().collect())
+}
+
+/// Temperatures of a transient simulation, one column per face of its layers
+pub fn output_transient(file_name: &str, transient: &Transient, path: &Path) -> Result<(), CalcError> {
+    let directory = path.join("Transient");
+    create_dir(&directory)?;
+
+    let mut header = vec!["Time".to_string(), "Surface".to_string()];
+    header.extend(transient.layers.iter().skip(1).map(|name| name.clone() + " Hot Side"));
+    header.push("Back Face".to_string());
+    let header = header.iter().map(String::as_str).collect::<Vec<&str>>();
+
+    write_csv(&directory.join(file_name.to_string() + ".csv"), &header, |wtr| {
+        for point in transient.points.iter() {
+            let mut row = vec![point.time];
+            row.extend(point.faces.iter());
+            wtr.serialize(row)?;
+        }
+        Ok(())
+    })
+}
+
+/// Surface & back face of the skin model next to the multilayer simulation, with the error of the skin model
+pub fn output_skin_comparison(file_name: &str, multilayer: &Transient, skin: &Transient, path: &Path) -> Result<(), CalcError> {
+    let directory = path.join("Transient");
+    create_dir(&directory)?;
+
+    let header = ["Time", "Surface Layers", "Surface Skin", "Surface Error", "Back Face Layers", "Back Face Skin", "Back Face Error"];
+    write_csv(&directory.join(file_name.to_string() + "_skin.csv"), &header, |wtr| {
+        for (layers, skin) in multilayer.points.iter().zip(skin.points.iter()) {
+            wtr.serialize((layers.time, layers.surface(), skin.surface(), skin.surface() - layers.surface(),
+                           layers.back_face(), skin.back_face(), skin.back_face() - layers.back_face()))?;
+        }
+        Ok(())
+    })
+}
+
+/// TPS file of `spec` as `read_tps_csv` reads it, written as `<name>.csv` into `path`
+pub fn output_tps_csv(spec: &TpsSpec, path: &Path) -> Result<PathBuf, CalcError> {
+    create_dir(path)?;
+
+    let output_file = path.join(spec.name.clone() + ".csv");
+    write_csv(&output_file, &["Name", &spec.name, "", "", "", ""], |wtr| {
+        if !spec.description.is_empty() {
+            wtr.write_record(["Description", &spec.description, "", "", "", ""])?;
+        }
+        wtr.write_record(["Temperature", &spec.temp.to_string(), "", "", "", ""])?;
+        match spec.back_boundary {
+            Some(BackBoundary::Temperature(temp)) =>
+                wtr.write_record(["Back Face", "Temperature", &temp.to_string(), "", "", ""])?,
+            Some(BackBoundary::Radiation { temp, emissivity }) =>
+                wtr.write_record(["Back Face", "Radiation", &temp.to_string(), &emissivity.to_string(), "", ""])?,
+            Some(BackBoundary::Convection { temp, coefficient }) =>
+                wtr.write_record(["Back Face", "Convection", &temp.to_string(), &coefficient.to_string(), "", ""])?,
+            None => {},
+        }
+        if let Some(factor) = spec.manufacturing_factor {
+            wtr.write_record(["Manufacturing Factor", &factor.to_string(), "", "", "", ""])?;
+        }
+        for (section, segments) in [("Min", &spec.segments_min), ("Max", &spec.segments_max)] {
+            if segments.is_empty() {
+                continue;
+            }
+            wtr.write_record([section, "Path", "Portion", "Tickness", "Temp Hot Side", " Temp Cold Side"])?;
+            for segment in segments.iter() {
+                let key = if segment.top_layer { "Top Layer" } else { "Layer" };
+                wtr.write_record([key, &segment.path, &segment.portion.to_string(), &segment.tickness.to_string(),
+                                  &segment.temp_hot_side.to_string(), &segment.temp_cold_side.to_string()])?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(output_file)
+}
+
+/// Markdown overview of the built TPS & parts with their `.info` sidecars, written as Index.md
+pub fn output_index(entries: &[IndexEntry], path: &Path) -> Result<(), CalcError> {
+    create_dir(path)?;
+
+    write_text(&path.join("Index.md"), |file| {
+        writeln!(file, "# Index")?;
+        for (kind, title) in [("TPS", "TPS"), ("Part", "Parts")] {
+            let mut entries = entries.iter().filter(|entry| entry.kind == kind).collect::<Vec<&IndexEntry>>();
+            if entries.is_empty() {
+                continue;
+            }
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+            writeln!(file, "\n## {}", title)?;
+            for entry in entries {
+                writeln!(file, "\n### {}", entry.name)?;
+                if !entry.description.is_empty() {
+                    writeln!(file, "\n{}", entry.description)?;
+                }
+                let lines = entry.info.as_ref().map(Info::lines).unwrap_or_default();
+                if !lines.is_empty() {
+                    writeln!(file)?;
+                    for line in lines {
+                        writeln!(file, "- {}", line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Write all `presets` sorted by name into one file
+pub fn output_presets(presets: &mut [Preset], output_file: &Path, format: &PresetFormat) -> Result<(), CalcError> {
+    if let Some(directory) = output_file.parent() {
+        create_dir(directory)?;
+    }
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    write_text(output_file, |file| {
+        for (i, preset) in presets.iter().enumerate() {
+            if i > 0 {
+                writeln!(file)?;
+            }
+            preset.write(file, format)?;
+        }
+        Ok(())
+    })
+}
+
+pub fn output_layer_table(name: &str, data: &PropertyTable<LayerData>, path: &Path) -> Result<(), CalcError> {
+    create_dir(path)?;
+
+    let output_file = path.join(name.to_owned() + ".csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity", "Temp Layer"], |wtr| {
+        for (temp, data) in data.iter() {
+            wtr.serialize((temp, data.thermal_data.cp, data.thermal_data.R_th, data.thermal_data.e, data.temp_sub_part))?;
+        }
+        Ok(())
+    })
+}
+
+pub fn output_table(name: &str, data: &PropertyTable<Data>, path: &Path) -> Result<(), CalcError> {
+    create_dir(path)?;
+
+    let output_file = path.join(name.to_owned() + ".csv");
+    write_csv(&output_file, &["Temp Part", "Heat Capacity", "1 / Thermal Insulance", "Emissivity"], |wtr| {
+        for (temp, data) in data.iter() {
+            wtr.serialize((temp, data.cp, data.R_th, data.e))?;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,150 @@
+//! Steady 1-D conduction through the layers of a TPS, giving the hot & cold side temperatures of every layer.
+//!
+//! The surface sits at the TPS temperature, the heat flux through the stack is the one the back face passes on.
+//! Within a layer `∫ k(T) dT` between its faces equals flux times thickness, so k follows the material table
+//! at the local temperature. Thickness is scaled by the portion like in [`calc_segment`](crate::calc_segment).
+
+use super::calculation::fill_gaps_in_csv;
+use super::data_holder::*;
+use super::error::CalcError;
+use super::property_table::PropertyTable;
+use super::transient::STEFAN_BOLTZMANN;
+
+/// Boundary condition at the back face of the bottom layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackBoundary {
+    /// held at a temperature in K
+    Temperature(f32),
+    /// radiates with `emissivity` towards an interior at `temp` K
+    Radiation { temp: f32, emissivity: f32 },
+    /// convects with `coefficient` in W/m²K to an interior at `temp` K
+    Convection { temp: f32, coefficient: f32 },
+}
+
+impl BackBoundary {
+    /// Temperature the back face approaches without any heat flux
+    pub fn temp(&self) -> f32 {
+        match self {
+            BackBoundary::Temperature(temp) | BackBoundary::Radiation { temp, .. } | BackBoundary::Convection { temp, .. } => *temp,
+        }
+    }
+
+    /// How far the back face at `back_temp` is from passing on `flux`, grows with the flux
+    fn residual(&self, flux: f32, back_temp: f32) -> f32 {
+        match self {
+            BackBoundary::Temperature(temp)                 => temp - back_temp,
+            BackBoundary::Radiation { temp, emissivity }    => flux - emissivity * STEFAN_BOLTZMANN * (back_temp.powi(4) - temp.powi(4)),
+            BackBoundary::Convection { temp, coefficient }  => flux - coefficient * (back_temp - temp),
+        }
+    }
+}
+
+/// Heat flux & face temperatures of a steady profile
+#[derive(Debug, Clone)]
+pub struct SteadyProfile {
+    /// W/m²
+    pub flux: f32,
+    /// K at the surface, the faces between the layers & the back face, top to bottom
+    pub faces: Vec<f32>,
+}
+
+/// Layer as the solver sees it
+struct Layer {
+    thickness: f32,
+    /// k (in the `R_th` field, as read) over temperature
+    data: PropertyTable<Data>,
+}
+
+impl Layer {
+    fn new(segment: &Segment) -> Self {
+        let mut data = segment.data_csv.clone();
+        fill_gaps_in_csv(&mut data, segment.interpolation);
+        Layer { thickness: segment.tickness * segment.portion, data }
+    }
+
+    /// Cold face temperature while `flux` passes from the hot face at `temp_hot`, 0 K if the layer can't conduct that much
+    fn cold_face(&self, temp_hot: f32, flux: f32) -> Result<f32, CalcError> {
+        let drop = flux * self.thickness;
+        if drop <= 0.0 {
+            return Ok(temp_hot);
+        }
+        let conducted = |temp_cold: f32| self.data.integrate(temp_cold, temp_hot, |data| data.R_th);
+        if conducted(0.0)? < drop {
+            return Ok(0.0);
+        }
+        let (mut low, mut high) = (0.0_f32, temp_hot);
+        for _ in 0..ITERATIONS {
+            let temp = (low + high) / 2.0;
+            if conducted(temp)? > drop {
+                low = temp;
+            } else {
+                high = temp;
+            }
+        }
+        Ok((low + high) / 2.0)
+    }
+}
+
+const ITERATIONS: usize = 60;
+
+/// Faces of all layers, top layer first, while `flux` passes through
+fn faces(layers: &[Layer], surface_temp: f32, flux: f32) -> Result<Vec<f32>, CalcError> {
+    let mut faces = vec![surface_temp];
+    for layer in layers {
+        let temp = layer.cold_face(faces[faces.len() - 1], flux)?;
+        faces.push(temp);
+    }
+    Ok(faces)
+}
+
+/// Solve the steady profile through `segments`, top layer first, with the surface at `surface_temp`
+pub fn steady_profile(segments: &[Segment], surface_temp: f32, back: BackBoundary) -> Result<SteadyProfile, CalcError> {
+    let layers = segments.iter().map(Layer::new).collect::<Vec<Layer>>();
+    if layers.iter().any(|layer| layer.data.is_empty()) {
+        return Err(CalcError::Simulation("steady profile needs the conductivity of every layer".to_string()));
+    }
+    match back {
+        BackBoundary::Radiation { emissivity, .. } if !(emissivity > 0.0 && emissivity <= 1.0) =>
+            return Err(CalcError::Simulation(format!("back face emissivity {} is outside (0, 1]", emissivity))),
+        BackBoundary::Convection { coefficient, .. } if !(coefficient > 0.0 && coefficient.is_finite()) =>
+            return Err(CalcError::Simulation(format!("heat transfer coefficient {} W/m²K is not positive", coefficient))),
+        _ => {},
+    }
+    if !(back.temp() > 0.0 && back.temp() < surface_temp) {
+        return Err(CalcError::Simulation(format!("back face at {} K has to be between 0 K and the surface at {} K", back.temp(), surface_temp)));
+    }
+
+    let residual = |flux: f32| -> Result<f32, CalcError> {
+        let faces = faces(&layers, surface_temp, flux)?;
+        Ok(back.residual(flux, faces[faces.len() - 1]))
+    };
+    // the residual grows with the flux: bracket its root, then bisect
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    while residual(high)? < 0.0 {
+        low = high;
+        high *= 2.0;
+        if !high.is_finite() {
+            return Err(CalcError::Simulation("no steady heat flux found".to_string()));
+        }
+    }
+    for _ in 0..ITERATIONS {
+        let flux = (low + high) / 2.0;
+        if residual(flux)? < 0.0 {
+            low = flux;
+        } else {
+            high = flux;
+        }
+    }
+    let flux = (low + high) / 2.0;
+    Ok(SteadyProfile { flux, faces: faces(&layers, surface_temp, flux)? })
+}
+
+/// Set hot & cold side of every segment from the steady profile, the material data has to be loaded
+pub fn apply_steady_profile(segments: &mut [Segment], surface_temp: f32, back: BackBoundary) -> Result<(), CalcError> {
+    let profile = steady_profile(segments, surface_temp, back)?;
+    for (segment, faces) in segments.iter_mut().zip(profile.faces.windows(2)) {
+        segment.temp_hot_side = faces[0];
+        segment.temp_cold_side = faces[1];
+    }
+    Ok(())
+}
@@ -0,0 +1,320 @@
+//! Transient 1-D heat conduction through the layers of a TPS.
+//!
+//! Finite volumes with nodes on both faces of every layer & `cells_per_layer - 1` nodes inside,
+//! stepped implicitly (backward Euler) so thin layers like RTV don't limit the time step.
+//! cp & k follow the material tables at the current node temperatures, the density is the
+//! areal density of the layer spread over its thickness, both scaled by its portion like in [`calc_segment`](crate::calc_segment).
+
+use super::calculation::fill_gaps_in_csv;
+use super::data_holder::*;
+use super::error::CalcError;
+use super::preset::PresetData;
+use super::property_table::PropertyTable;
+
+pub const STEFAN_BOLTZMANN: f32 = 5.670_374e-8;
+
+/// What happens at the hot surface, histories are rows of (time in s, value) & hold their last row
+#[derive(Debug, Clone)]
+pub enum Surface {
+    /// absorbed heat flux in W/m², the surface radiates with the emissivity of the top layer
+    HeatFlux(PropertyTable<f32>),
+    /// surface temperature in K
+    Temperature(PropertyTable<f32>),
+}
+
+impl Surface {
+    /// Time of the last row
+    pub fn duration(&self) -> f32 {
+        let history = match self {
+            Surface::HeatFlux(history) | Surface::Temperature(history) => history,
+        };
+        history.temps().last().copied().unwrap_or(0.0)
+    }
+}
+
+/// What happens at the back face of the bottom layer
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BackFace {
+    /// no heat leaves, the conservative case for the substructure
+    #[default]
+    Adiabatic,
+    /// held at a temperature in K
+    Temperature(f32),
+    /// lumped substructure with this heat capacity in J/m²K, starting at the initial temperature
+    HeatSink(f32),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TransientSettings {
+    /// s
+    pub time_step: f32,
+    /// s, 0 runs to the end of the surface history
+    pub duration: f32,
+    /// K, of the whole stack at the start
+    pub initial_temp: f32,
+    /// K, the surface radiates towards it
+    pub ambient_temp: f32,
+    pub cells_per_layer: usize,
+    pub back_face: BackFace,
+}
+
+impl Default for TransientSettings {
+    fn default() -> Self {
+        TransientSettings {
+            time_step: 1.0,
+            duration: 0.0,
+            initial_temp: 300.0,
+            ambient_temp: 300.0,
+            cells_per_layer: 8,
+            back_face: BackFace::Adiabatic,
+        }
+    }
+}
+
+/// Temperatures at one time step
+#[derive(Debug, Clone)]
+pub struct TransientPoint {
+    /// s
+    pub time: f32,
+    /// K at the surface, the faces between the layers & the back face, top to bottom
+    pub faces: Vec<f32>,
+}
+
+impl TransientPoint {
+    pub fn surface(&self) -> f32 {
+        self.faces[0]
+    }
+
+    pub fn back_face(&self) -> f32 {
+        self.faces[self.faces.len() - 1]
+    }
+}
+
+/// Course of a simulation
+#[derive(Debug, Clone)]
+pub struct Transient {
+    /// material names of the simulated layers, top to bottom
+    pub layers: Vec<String>,
+    /// the start & every time step
+    pub points: Vec<TransientPoint>,
+}
+
+impl Transient {
+    /// Point of the highest back face temperature
+    pub fn peak_back_face(&self) -> &TransientPoint {
+        self.points.iter().max_by(|a, b| a.back_face().total_cmp(&b.back_face())).unwrap()
+    }
+}
+
+/// Layer as the solver sees it
+struct Layer {
+    thickness: f32,
+    /// kg/m³
+    density: f32,
+    /// cp & k (in the `R_th` field, as read) over temperature
+    data: PropertyTable<Data>,
+}
+
+impl Layer {
+    fn new(segment: &Segment) -> Self {
+        let thickness = segment.tickness * segment.portion;
+        let areal_density = (segment.density * segment.tickness + segment.additive_areal_weight) * segment.portion;
+        let mut data = segment.data_csv.clone();
+        fill_gaps_in_csv(&mut data, segment.interpolation);
+        Layer { thickness, density: areal_density / thickness, data }
+    }
+}
+
+/// Run `surface` through the layers, top layer first. Layers without thickness are left out.
+pub fn simulate(segments: &[Segment], surface: &Surface, settings: &TransientSettings) -> Result<Transient, CalcError> {
+    let segments = segments.iter().filter(|segment| segment.tickness * segment.portion > 0.0).collect::<Vec<&Segment>>();
+    let layers = segments.iter().map(|segment| Layer::new(segment)).collect::<Vec<Layer>>();
+    if layers.is_empty() {
+        return Err(CalcError::Simulation("no layer with a thickness".to_string()));
+    }
+    if settings.time_step <= 0.0 || settings.cells_per_layer == 0 {
+        return Err(CalcError::Simulation("time step & cells per layer have to be positive".to_string()));
+    }
+
+    // node j & j + 1 enclose cell j
+    let cells = settings.cells_per_layer;
+    let cell_layer = (0..layers.len() * cells).map(|j| j / cells).collect::<Vec<usize>>();
+    let cell_width = cell_layer.iter().map(|i| layers[*i].thickness / cells as f32).collect::<Vec<f32>>();
+    let n = cell_layer.len() + 1;
+    let mut temps = vec![settings.initial_temp; n];
+
+    let duration = if settings.duration > 0.0 { settings.duration } else { surface.duration() };
+    let steps = (duration / settings.time_step).ceil() as usize;
+    let faces = |temps: &[f32]| (0..=layers.len()).map(|i| temps[i * cells]).collect::<Vec<f32>>();
+    let mut points = vec![TransientPoint { time: 0.0, faces: faces(&temps) }];
+
+    let (mut lower, mut diagonal, mut upper, mut rhs) = (vec![0.0; n], vec![0.0; n], vec![0.0; n], vec![0.0; n]);
+    for step in 1..=steps {
+        let time = step as f32 * settings.time_step;
+
+        for row in [&mut lower, &mut diagonal, &mut upper, &mut rhs] {
+            row.fill(0.0);
+        }
+        for (j, (layer, width)) in cell_layer.iter().zip(cell_width.iter()).enumerate() {
+            let layer = &layers[*layer];
+            // half of the cell belongs to each of its nodes
+            for node in [j, j + 1] {
+                let capacity = layer.density * layer.data.at(temps[node])?.cp * width / 2.0 / settings.time_step;
+                diagonal[node] += capacity;
+                rhs[node] += capacity * temps[node];
+            }
+            let conductance = layer.data.at((temps[j] + temps[j + 1]) / 2.0)?.R_th / width;
+            diagonal[j] += conductance;
+            diagonal[j + 1] += conductance;
+            upper[j] -= conductance;
+            lower[j + 1] -= conductance;
+        }
+
+        match surface {
+            Surface::HeatFlux(flux) => {
+                // radiation linearized around the last temperature
+                let emissivity = layers[0].data.at(temps[0])?.e;
+                let radiation = emissivity * STEFAN_BOLTZMANN;
+                diagonal[0] += 4.0 * radiation * temps[0].powi(3);
+                rhs[0] += flux.at(time)? + radiation * (3.0 * temps[0].powi(4) + settings.ambient_temp.powi(4));
+            },
+            Surface::Temperature(history) => fix(&mut lower, &mut diagonal, &mut upper, &mut rhs, 0, history.at(time)?),
+        }
+        back_face(&mut lower, &mut diagonal, &mut rhs, &temps, settings);
+
+        temps = solve_tridiagonal(&lower, &diagonal, &upper, &rhs);
+        points.push(TransientPoint { time, faces: faces(&temps) });
+    }
+    Ok(Transient { layers: segments.iter().map(|segment| segment.name.clone()).collect(), points })
+}
+
+/// Single skin of KSP/ROThermal: the whole TPS lumped into one node at the surface,
+/// separated from the back face by its thermal insulance
+#[derive(Debug, Clone, Copy)]
+pub struct SkinModel {
+    /// kg/m²
+    pub mass_per_area: f32,
+    /// J/kg·K
+    pub specific_heat: f32,
+    /// m²K/W
+    pub insulance: f32,
+    pub emissivity: f32,
+}
+
+impl SkinModel {
+    /// Values of the Min or Max section of a preset, `None` if it lacks one of them.
+    /// Presets hold the insulance in m²K/kW
+    pub fn from_preset(preset: &PresetData, max: bool) -> Option<Self> {
+        let (mass_per_area, specific_heat, insulance) = if max {
+            (preset.areal_density_max?, preset.cp_max?, preset.insulance_max?)
+        } else {
+            (preset.areal_density_min?, preset.cp_min?, preset.insulance_min?)
+        };
+        Some(SkinModel { mass_per_area, specific_heat, insulance: insulance / 1000.0, emissivity: preset.emissive_const? })
+    }
+}
+
+/// Run `surface` through the two nodes of `skin`, same boundaries & time steps as [`simulate`].
+/// The back node has no capacity of its own, so the back face needs a temperature or heat sink:
+/// an adiabatic one just follows the skin & never depends on the insulance
+pub fn simulate_skin(skin: &SkinModel, surface: &Surface, settings: &TransientSettings) -> Result<Transient, CalcError> {
+    if settings.time_step <= 0.0 || skin.insulance <= 0.0 {
+        return Err(CalcError::Simulation("time step & insulance have to be positive".to_string()));
+    }
+    if settings.back_face == BackFace::Adiabatic {
+        return Err(CalcError::Simulation("the skin model needs a back face temperature or heat capacity, an adiabatic back face follows the skin".to_string()));
+    }
+    let mut temps = vec![settings.initial_temp; 2];
+    let duration = if settings.duration > 0.0 { settings.duration } else { surface.duration() };
+    let steps = (duration / settings.time_step).ceil() as usize;
+    let mut points = vec![TransientPoint { time: 0.0, faces: temps.clone() }];
+
+    let capacity = skin.mass_per_area * skin.specific_heat / settings.time_step;
+    let conductance = 1.0 / skin.insulance;
+    for step in 1..=steps {
+        let time = step as f32 * settings.time_step;
+        let mut lower = vec![0.0, -conductance];
+        let mut diagonal = vec![capacity + conductance, conductance];
+        let mut upper = vec![-conductance, 0.0];
+        let mut rhs = vec![capacity * temps[0], 0.0];
+
+        match surface {
+            Surface::HeatFlux(flux) => {
+                let radiation = skin.emissivity * STEFAN_BOLTZMANN;
+                diagonal[0] += 4.0 * radiation * temps[0].powi(3);
+                rhs[0] += flux.at(time)? + radiation * (3.0 * temps[0].powi(4) + settings.ambient_temp.powi(4));
+            },
+            Surface::Temperature(history) => fix(&mut lower, &mut diagonal, &mut upper, &mut rhs, 0, history.at(time)?),
+        }
+        back_face(&mut lower, &mut diagonal, &mut rhs, &temps, settings);
+
+        temps = solve_tridiagonal(&lower, &diagonal, &upper, &rhs);
+        points.push(TransientPoint { time, faces: temps.clone() });
+    }
+    Ok(Transient { layers: vec!["Skin".to_string()], points })
+}
+
+/// Difference of the skin model to the multilayer simulation at one time step, skin - multilayer
+#[derive(Debug, Clone, Copy)]
+pub struct ModelError {
+    pub time: f32,
+    pub surface: f32,
+    pub back_face: f32,
+}
+
+/// Errors of `skin` against `multilayer` at every time step they share
+pub fn compare(multilayer: &Transient, skin: &Transient) -> Vec<ModelError> {
+    multilayer.points.iter().zip(skin.points.iter())
+        .map(|(layers, skin)| ModelError {
+            time: layers.time,
+            surface: skin.surface() - layers.surface(),
+            back_face: skin.back_face() - layers.back_face(),
+        })
+        .collect()
+}
+
+/// Boundary of the last node
+fn back_face(lower: &mut [f32], diagonal: &mut [f32], rhs: &mut [f32], temps: &[f32], settings: &TransientSettings) {
+    let n = diagonal.len();
+    match settings.back_face {
+        BackFace::Adiabatic => {},
+        BackFace::Temperature(temp) => {
+            lower[n - 1] = 0.0;
+            diagonal[n - 1] = 1.0;
+            rhs[n - 1] = temp;
+        },
+        BackFace::HeatSink(capacity) => {
+            diagonal[n - 1] += capacity / settings.time_step;
+            rhs[n - 1] += capacity / settings.time_step * temps[n - 1];
+        },
+    }
+}
+
+/// Replace the equation of `node` by `T = temp`
+fn fix(lower: &mut [f32], diagonal: &mut [f32], upper: &mut [f32], rhs: &mut [f32], node: usize, temp: f32) {
+    lower[node] = 0.0;
+    upper[node] = 0.0;
+    diagonal[node] = 1.0;
+    rhs[node] = temp;
+}
+
+/// Thomas algorithm, `lower[0]` & `upper[n - 1]` are ignored
+fn solve_tridiagonal(lower: &[f32], diagonal: &[f32], upper: &[f32], rhs: &[f32]) -> Vec<f32> {
+    let n = diagonal.len();
+    let mut c = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    c[0] = upper[0] / diagonal[0];
+    d[0] = rhs[0] / diagonal[0];
+    for i in 1..n {
+        let m = diagonal[i] - lower[i] * c[i - 1];
+        c[i] = upper[i] / m;
+        d[i] = (rhs[i] - lower[i] * d[i - 1]) / m;
+    }
+
+    let mut x = vec![0.0; n];
+    x[n - 1] = d[n - 1];
+    for i in (0..n - 1).rev() {
+        x[i] = d[i] - c[i] * x[i + 1];
+    }
+    x
+}
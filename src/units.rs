@@ -0,0 +1,125 @@
+//! Units of the material tables, converted into SI on load.
+//!
+//! Unit names are compared without case, spaces, `·`, `*`, `-`, `.`, `^` & `°`, so `BTU·in/hr·ft²·°F`,
+//! `btu in/hr ft^2 F` and `BTUin/hrft2F` are the same unit.
+
+use super::data_holder::Data;
+
+/// Temperature scale of a table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    #[default]
+    Kelvin,
+    Celsius,
+    Fahrenheit,
+    Rankine,
+}
+
+impl TemperatureUnit {
+    pub fn parse(name: &str) -> Option<Self> {
+        match normalize(name).as_str() {
+            "k" | "kelvin"     => Some(TemperatureUnit::Kelvin),
+            "c" | "celsius"    => Some(TemperatureUnit::Celsius),
+            "f" | "fahrenheit" => Some(TemperatureUnit::Fahrenheit),
+            "r" | "rankine"    => Some(TemperatureUnit::Rankine),
+            _                  => None,
+        }
+    }
+
+    pub fn to_kelvin(&self, temp: f32) -> f32 {
+        match self {
+            TemperatureUnit::Kelvin     => temp,
+            TemperatureUnit::Celsius    => temp + 273.15,
+            TemperatureUnit::Fahrenheit => (temp - 32.0) * 5.0 / 9.0 + 273.15,
+            TemperatureUnit::Rankine    => temp * 5.0 / 9.0,
+        }
+    }
+}
+
+/// Factor from a specific heat unit to J/kg·K
+pub fn specific_heat_factor(name: &str) -> Option<f32> {
+    match normalize(name).as_str() {
+        "j/kgk" | "j/kgc"                      => Some(1.0),
+        "kj/kgk" | "kj/kgc" | "j/gk" | "j/gc"  => Some(1000.0),
+        "btu/lbf" | "btu/lbr" | "cal/gk" | "cal/gc" => Some(4186.8),
+        _                                      => None,
+    }
+}
+
+/// Factor from a thermal conductivity unit to W/m·K
+pub fn conductivity_factor(name: &str) -> Option<f32> {
+    match normalize(name).as_str() {
+        "w/mk" | "w/mc"                    => Some(1.0),
+        "mw/mk" | "mw/mc"                  => Some(0.001),
+        "w/cmk" | "w/cmc"                  => Some(100.0),
+        "btuin/hrft2f" | "btuin/hft2f"     => Some(0.144_227_9),
+        "btu/hrftf" | "btu/hftf"           => Some(1.730_735),
+        _                                  => None,
+    }
+}
+
+/// Units of the columns Temperature, Specific Heat, Thermal Conductivity & Emissivity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    /// into J/kg·K
+    pub specific_heat: f32,
+    /// into W/m·K
+    pub conductivity: f32,
+}
+
+impl Default for Units {
+    /// SI, the units of files without "Units" row
+    fn default() -> Self {
+        Units { temperature: TemperatureUnit::Kelvin, specific_heat: 1.0, conductivity: 1.0 }
+    }
+}
+
+impl Units {
+    /// Temperature in K, an empty cell (`None`) stays empty
+    pub fn kelvin(&self, temp: Option<f32>) -> Option<f32> {
+        temp.map(|temp| self.temperature.to_kelvin(temp))
+    }
+
+    /// Row of a material table in SI, empty cells stay empty
+    pub fn convert(&self, temp: Option<f32>, data: Data) -> (Option<f32>, Data) {
+        (self.kelvin(temp), Data {
+            cp: data.cp * self.specific_heat,
+            R_th: data.R_th * self.conductivity,
+            e: data.e,
+        })
+    }
+}
+
+/// What the price of a material refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostBasis {
+    /// per kg
+    Mass,
+    /// per m² & mm of thickness
+    AreaThickness,
+}
+
+impl CostBasis {
+    pub fn parse(name: &str) -> Option<Self> {
+        match normalize(name).trim_start_matches('/') {
+            "kg"   => Some(CostBasis::Mass),
+            "m2mm" => Some(CostBasis::AreaThickness),
+            _      => None,
+        }
+    }
+}
+
+/// Emissivity has no unit, "-", "1" or an empty cell declare it
+pub fn is_dimensionless(name: &str) -> bool {
+    matches!(normalize(name).as_str(), "" | "1")
+}
+
+fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .replace("deg", "")
+        .replace('²', "2")
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '·' | '*' | '^' | '°' | '.' | '-'))
+        .collect()
+}
@@ -0,0 +1,218 @@
+use super::calculation::layer_temp_at;
+use super::data_holder::*;
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Row keys `read_tps_csv` understands
+pub const TPS_KEYS: [&str; 9] = ["Name", "Description", "Temperature", "Back Face", "Manufacturing Factor", "Min", "Max", "Top Layer", "Layer"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Finding of a validation pass
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub path: PathBuf,
+    pub line: Option<u64>,
+    pub message: String,
+}
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// Check a TPS for inconsistencies the calculation would silently accept or panic on.
+///
+/// Temperature limits are only checked for layers whose material is loaded.
+pub fn validate_tps(spec: &TpsSpec) -> Vec<Issue> {
+    let mut issues = Vec::<Issue>::new();
+    let mut issue = |severity: Severity, line: Option<u64>, message: String| {
+        issues.push(Issue { severity, path: spec.path.clone(), line, message });
+    };
+
+    for (line, key) in spec.unknown_keys.iter() {
+        let message = match suggest(key, &TPS_KEYS) {
+            Some(known) => format!("unknown key \"{}\", did you mean \"{}\"?", key, known),
+            None => format!("unknown key \"{}\"", key),
+        };
+        issue(Severity::Warning, Some(*line), message);
+    }
+    if spec.temp <= 0.0 {
+        issue(Severity::Error, None, format!("Temperature {} K is not positive", spec.temp));
+    }
+    if let Some(factor) = spec.manufacturing_factor.filter(|factor| !(*factor > 0.0 && factor.is_finite())) {
+        issue(Severity::Error, None, format!("Manufacturing Factor {} is outside (0, ∞)", factor));
+    }
+
+    for (section, segments) in [("Min", &spec.segments_min), ("Max", &spec.segments_max)] {
+        if segments.is_empty() {
+            // Min-only files are fine, Max falls back to Min in compute_tps
+            match section {
+                "Max" if !spec.segments_min.is_empty() => issue(Severity::Warning, None, "Max section has no layers, the Min layers are used".to_string()),
+                _                                      => issue(Severity::Error, None, format!("{} section has no layers", section)),
+            }
+            continue;
+        }
+        if !segments.iter().any(|segment| segment.top_layer) {
+            issue(Severity::Error, None, format!("{} section lacks a \"Top Layer\"", section));
+        }
+
+        for (i, segment) in segments.iter().enumerate() {
+            let layer = format!("{} layer {} ({})", section, i + 1, segment_label(segment));
+
+            // a top layer without thickness is a coating that only provides the emissivity
+            if segment.tickness < 0.0 {
+                issue(Severity::Error, None, format!("{}: thickness {} m is negative", layer, segment.tickness));
+            } else if segment.tickness == 0.0 && !segment.top_layer {
+                issue(Severity::Warning, None, format!("{}: thickness is 0 m", layer));
+            }
+            if !(segment.portion > 0.0 && segment.portion.is_finite()) {
+                issue(Severity::Error, None, format!("{}: portion {} is outside (0, ∞)", layer, segment.portion));
+            }
+            if segment.temp_hot_side <= 0.0 {
+                issue(Severity::Error, None, format!("{}: hot side is missing, enter it or add a \"Back Face\" row", layer));
+            } else if segment.temp_hot_side < segment.temp_cold_side {
+                issue(Severity::Error, None, format!("{}: hot side {} K is colder than cold side {} K",
+                                                     layer, segment.temp_hot_side, segment.temp_cold_side));
+            }
+            if let Some(ablator) = segment.ablator {
+                if !(ablator.heat_of_ablation > 0.0 && ablator.pyrolysis_temp > 0.0) {
+                    issue(Severity::Error, None, format!("{}: ablator needs a positive Heat of Ablation & Pyrolysis Temperature", layer));
+                } else if ablator.pyrolysis_temp >= spec.temp {
+                    issue(Severity::Error, None, format!("{}: Pyrolysis Temperature {} K is not below the TPS Temperature {} K",
+                                                         layer, ablator.pyrolysis_temp, spec.temp));
+                }
+                if !(ablator.char_density >= 0.0 && ablator.char_density < segment.density) {
+                    issue(Severity::Error, None, format!("{}: Char Density {} kg/m³ is outside [0, {})", layer, ablator.char_density, segment.density));
+                }
+            }
+            if let Some(absorptance) = segment.absorptance.filter(|absorptance| !(0.0..=1.0).contains(absorptance)) {
+                issue(Severity::Error, None, format!("{}: Solar Absorptance {} is outside [0, 1]", layer, absorptance));
+            }
+            if let Some(cost) = segment.cost.filter(|cost| cost.value < 0.0) {
+                issue(Severity::Error, None, format!("{}: Cost {} is negative", layer, cost.value));
+            }
+            if segment.temp_max > 0.0 && segment.temp_hot_side > segment.temp_max {
+                issue(Severity::Warning, None, format!("{}: hot side {} K is above the Temperature Limit {} K",
+                                                       layer, segment.temp_hot_side, segment.temp_max));
+            }
+        }
+    }
+
+    let materials_min = spec.segments_min.iter().map(segment_label).collect::<Vec<&str>>();
+    let materials_max = spec.segments_max.iter().map(segment_label).collect::<Vec<&str>>();
+    if !materials_max.is_empty() && materials_min != materials_max {
+        issue(Severity::Warning, None, format!("Min and Max use different materials {:?} / {:?}", materials_min, materials_max));
+    }
+    issues
+}
+
+/// Temperature margin of a layer while the TPS is at its max temperature
+#[derive(Debug, Clone)]
+pub struct LayerMargin {
+    pub section: &'static str,
+    pub layer: usize,
+    pub material: String,
+    pub temp_limit: f32,
+    pub temp_hot_side: f32,
+    pub temp_cold_side: f32,
+    /// hottest temperature in the layer, its own hot face
+    pub temp_peak: f32,
+    /// cold face of the layer above, only a note: the hot sides in the TPS file need not match it
+    pub temp_above: f32,
+    /// `temp_limit - temp_peak`, infinite for materials without limit
+    pub margin: f32,
+}
+
+/// Margins of all layers of a calculated TPS against their material Temperature Limit.
+///
+/// The peak is the hot side of the layer in the TPS file, or the hot face of the gradient `avg_cp_k` uses
+/// at the TPS temperature if that is hotter. The cold face of the layer above, the hot face scaled by
+/// `temp_cold_side / temp_hot_side`, is kept as `temp_above` but not checked against the limit.
+pub fn temperature_margins(tps: &TPS) -> Vec<LayerMargin> {
+    let mut margins = Vec::<LayerMargin>::new();
+
+    for (section, segments) in [("Min", &tps.segments_min), ("Max", &tps.segments_max)] {
+        let mut temp_above = 0.0_f32;
+        for (i, segment) in segments.iter().enumerate() {
+            let temp_hot_face = if segment.data_avg_r.is_empty() { segment.temp_hot_side } else { layer_temp_at(&segment.data_avg_r, tps.temp) };
+            let temp_frac = if segment.temp_hot_side > 0.0 { segment.temp_cold_side / segment.temp_hot_side } else { 1.0 };
+            let temp_peak = segment.temp_hot_side.max(temp_hot_face);
+
+            let margin = if segment.temp_max > 0.0 { segment.temp_max - temp_peak } else { f32::INFINITY };
+            margins.push(LayerMargin {
+                section,
+                layer: i + 1,
+                material: segment.name.clone(),
+                temp_limit: segment.temp_max,
+                temp_hot_side: segment.temp_hot_side,
+                temp_cold_side: segment.temp_cold_side,
+                temp_peak,
+                temp_above,
+                margin,
+            });
+            temp_above = temp_hot_face * temp_frac;
+        }
+    }
+    margins
+}
+
+/// Warnings for layers that get hotter than their material allows, `path` is the TPS file
+pub fn check_margins(margins: &[LayerMargin], path: &Path) -> Vec<Issue> {
+    margins.iter()
+        .filter(|margin| margin.margin < 0.0)
+        .map(|margin| Issue {
+            severity: Severity::Warning,
+            path: path.to_path_buf(),
+            line: None,
+            message: format!("{} layer {} ({}): reaches {:.0} K, {:.0} K above the Temperature Limit {} K",
+                             margin.section, margin.layer, margin.material, margin.temp_peak, -margin.margin, margin.temp_limit),
+        })
+        .collect()
+}
+
+/// Material name once loaded, the reference from the TPS file before
+fn segment_label(segment: &Segment) -> &str {
+    if segment.name.is_empty() {
+        &segment.path
+    } else {
+        &segment.name
+    }
+}
+
+/// Closest of `known` to `key`, if it is close enough to be a typo
+pub fn suggest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known.iter()
+        .map(|candidate| (edit_distance(&key.to_lowercase(), &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 3))
+        .min_by_key(|(distance, _candidate)| *distance)
+        .map(|(_distance, candidate)| candidate)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+
+    for (i, char_a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(char_a != *char_b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+const PHENOLIC: &str = "Name,Phenolic,,\nTemperature Limit,3000,,\nDensity,1450,,\nChar Density,1150,,\nChar,1600,0.9,0.9\n\
+    Heat of Ablation,2.3e7,,\nPyrolysis Temperature,600,,\nTemperature,Specific Heat,Thermal Conductivity,Emissivity\n\
+    300,1200,0.4,0.8\n1500,1800,0.6,0.85\n";
+
+fn ablator() -> Ablator {
+    Ablator { char_density: 1150.0, char_data: Data { cp: 1600.0, R_th: 0.9, e: 0.9 }, heat_of_ablation: 2.3e7, pyrolysis_temp: 600.0 }
+}
+
+fn phenolic(tickness: f32) -> Segment {
+    Segment { ablator: Some(ablator()), ..segment(tickness, 1450.0) }
+}
+
+#[test]
+fn ablator_rows() {
+    let directory = TempDir::new("ablator");
+    let path = directory.write("Phenolic.csv", PHENOLIC);
+    let mut material = Segment { path: path.display().to_string(), ..Default::default() };
+    read_material_csv(&mut material).unwrap();
+    assert_eq!(material.ablator, Some(ablator()));
+
+    // temperature & char data follow the Units row
+    directory.write("Phenolic.csv", "Name,Phenolic,,\nUnits,°F,BTU/lb·°F,W/m·K,-\nDensity,1450,,\nChar,0.5,0.9,\nHeat of Ablation,2.3e7,,\n\
+        Pyrolysis Temperature,620.33,,\nTemperature,Specific Heat,Thermal Conductivity,Emissivity\n80.33,0.3,0.4,0.8\n");
+    let mut material = Segment { path: path.display().to_string(), ..Default::default() };
+    read_material_csv(&mut material).unwrap();
+    let converted = material.ablator.unwrap();
+    assert!((converted.pyrolysis_temp - 600.0).abs() < 0.01);
+    assert!((converted.char_data.cp - 2093.4).abs() < 0.1);
+    assert_eq!(converted.char_density, 0.0);
+
+    directory.write("Phenolic.csv", &format!("Name,LI-900,,\nDensity,144,,\n{}", TABLE));
+    let mut material = Segment { path: path.display().to_string(), ..Default::default() };
+    read_material_csv(&mut material).unwrap();
+    assert_eq!(material.ablator, None);
+}
+
+#[test]
+fn char_above_pyrolysis() {
+    let virgin = [(300.0, Data { cp: 1200.0, R_th: 0.4, e: 0.8 }), (1500.0, Data { cp: 1800.0, R_th: 0.6, e: 0.85 })].into_iter()
+        .collect::<PropertyTable<Data>>();
+    let charred = ablator().charred(&virgin);
+
+    assert_eq!(charred.temps(), &[300.0, 600.0, 610.0, 1500.0]);
+    assert_eq!(charred.values()[0], virgin.values()[0]);
+    assert_eq!(charred.values()[1], virgin.at(600.0).unwrap());
+    assert_eq!(charred.values()[2], ablator().char_data);
+    assert_eq!(charred.at(2000.0).unwrap(), ablator().char_data);
+
+    // empty cells of the Char row keep the virgin value
+    let no_emissivity = Ablator { char_data: Data { e: 0.0, ..ablator().char_data }, ..ablator() };
+    assert_eq!(no_emissivity.charred(&virgin).values()[3].e, virgin.at(600.0).unwrap().e);
+    // no Char row, no change
+    let no_char = Ablator { char_data: Data::default(), ..ablator() };
+    let unchanged = no_char.charred(&virgin);
+    assert_eq!((unchanged.temps(), unchanged.values()), (virgin.temps(), virgin.values()));
+}
+
+#[test]
+fn tps_ablator_params() {
+    let tps = TPS {
+        temp: 2000.0,
+        segments_min: vec![phenolic(0.01), segment(0.001, 8000.0)],
+        segments_max: vec![phenolic(0.03), segment(0.001, 8000.0)],
+        ..Default::default()
+    };
+    let params = AblatorParams::from_tps(&tps).unwrap();
+
+    assert!((params.resource_per_area_min - 3.0).abs() < 1e-4);
+    assert!((params.resource_per_area_max - 9.0).abs() < 1e-4);
+    assert_eq!(params.temp_threshold, 600.0);
+    assert!((params.pyrolysis_loss_factor - 23000.0).abs() < 0.1);
+    // the loss rate at skinMaxTemp is a hundred times the one at the pyrolysis temperature
+    let rise = (params.loss_exp / 2000.0).exp() / (params.loss_exp / 600.0).exp();
+    assert!(params.loss_exp < 0.0 && (rise - 100.0).abs() < 0.1, "{}", rise);
+
+    let steel = vec![segment(0.001, 8000.0)];
+    let plain = TPS { segments_min: steel.clone(), segments_max: steel, ..tps };
+    assert_eq!(AblatorParams::from_tps(&plain), None);
+}
+
+#[test]
+fn part_ablator_params() {
+    let params = |resource: f32, factor: f32, temp: f32| AblatorParams {
+        resource_per_area_min: resource,
+        resource_per_area_max: 2.0 * resource,
+        temp_threshold: temp,
+        loss_exp: -5000.0,
+        pyrolysis_loss_factor: factor,
+    };
+    let part = AblatorParams::from_parts(&[(params(4.0, 20000.0, 600.0), 0.5), (params(2.0, 10000.0, 700.0), 0.5)]).unwrap();
+
+    assert_eq!(part.resource_per_area_min, 3.0);
+    assert_eq!(part.resource_per_area_max, 6.0);
+    assert_eq!(part.temp_threshold, 600.0);
+    // weighted by the resource each TPS brings
+    assert!((part.pyrolysis_loss_factor - (20000.0 * 2.0 + 10000.0) / 3.0).abs() < 0.1);
+    assert_eq!(AblatorParams::from_parts(&[]), None);
+}
+
+#[test]
+fn heat_shield_preset() {
+    let directory = TempDir::new("heat_shield");
+    directory.write("Phenolic.csv", PHENOLIC);
+    let tps_path = directory.write("Heat_Shield.csv", "Name,Heat_Shield,,,,\nTemperature,2000,,,,\n\
+        Min,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\nTop Layer,Phenolic.csv,1,0.01,2000,500\nLayer,Steel_X5CrNi18-9,1,0.001,500,450\n\
+        Max,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\nTop Layer,Phenolic.csv,1,0.03,2000,500\nLayer,Steel_X5CrNi18-9,1,0.001,500,450\n");
+
+    let spec = load_tps_spec(&tps_path, &MaterialLibrary::new(&library_root())).unwrap();
+    assert!(validate_tps(&spec).iter().all(|issue| issue.severity == Severity::Warning), "{:?}", validate_tps(&spec));
+    let temp_grid = temp_grid();
+    let tps = compute_tps(&spec, &temp_grid);
+
+    let node = Preset::from_tps(&tps, ReferenceTemp::Max).to_node();
+    assert_eq!(node.get("ablativeResource"), Some("Ablator"));
+    assert_eq!(node.get("ablationTempThresh"), Some("600"));
+    assert_eq!(node.get("disableModAblator"), Some("false"));
+    let value = |key: &str| node.get(key).unwrap().parse::<f32>().unwrap();
+    assert!((value("ablativeResourcePerArea") - 3.0).abs() < 1e-4);
+    assert!((value("reentryMassPerArea") - (tps.areal_density_min - 3.0)).abs() < 1e-4);
+    assert!((value("reentryMassPerAreaMax") - (tps.areal_density_max - 9.0)).abs() < 1e-4);
+
+    // a part halfway between the thicknesses
+    let structure = |height: f32| StructureRef { name: "Heat_Shield".to_string(), portion: 1.0, height_min: height, height_max: f32::INFINITY };
+    let part_spec = PartSpec { name: "Capsule".to_string(), temp: 2000.0, has_ablator: true, structures: vec![structure(0.021)], ..Default::default() };
+    let part = compute_part(&part_spec, std::slice::from_ref(&tps), &temp_grid).unwrap();
+    let part_node = Preset::from_part(&part, ReferenceTemp::Max).to_node();
+    assert_eq!(part_node.get("disableModAblator"), Some("false"));
+    let part_data = PresetData::from_node(&part_node, &tps_path).unwrap();
+    assert_eq!(part_data.has_ablator, Some(true));
+    let resource = part.ablator.unwrap().resource_per_area_min;
+    assert!((resource - 6.0).abs() < 0.01, "{}", resource);
+
+    let plain = TPS { ablator: None, ..tps };
+    assert_eq!(Preset::from_tps(&plain, ReferenceTemp::Max).to_node().get("disableModAblator"), Some("true"));
+    assert!(matches!(compute_part(&part_spec, std::slice::from_ref(&plain), &temp_grid), Err(CalcError::NoAblator { .. })));
+    let plain_part = compute_part(&PartSpec { has_ablator: false, ..part_spec }, &[plain], &temp_grid).unwrap();
+    assert_eq!(Preset::from_part(&plain_part, ReferenceTemp::Max).to_node().get("disableModAblator"), Some("true"));
+}
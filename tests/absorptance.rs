@@ -0,0 +1,39 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+#[test]
+fn absorptive_constant() {
+    let library = MaterialLibrary::new(&library_root());
+    let directory = TempDir::new("absorptance");
+    write_material(&directory, "Black", "Solar Absorptance,0.9,,\n");
+    write_material(&directory, "Plain", "");
+    let temp_grid = temp_grid();
+
+    let black_spec = load_tps_spec(&write_tps(&directory, "Black_Tiles", "Black.csv", ""), &library).unwrap();
+    assert_eq!(black_spec.segments_min[0].absorptance, Some(0.9));
+    let black = compute_tps(&black_spec, &temp_grid);
+    assert_eq!(black.absorbation_const, Some(0.9));
+    assert_eq!(Preset::from_tps(&black, ReferenceTemp::Max).to_node().get("absorptiveConstant"), Some("0.9"));
+
+    let plain = compute_tps(&load_tps_spec(&write_tps(&directory, "Plain_Tiles", "Plain.csv", ""), &library).unwrap(), &temp_grid);
+    assert_eq!(plain.absorbation_const, None);
+    assert_eq!(Preset::from_tps(&plain, ReferenceTemp::Max).to_node().get("absorptiveConstant"), Some("0"));
+
+    // the typed-in constant stands in for the plain tiles
+    let structure = |name: &str, portion: f32| StructureRef { name: name.to_string(), portion, height_min: f32::NEG_INFINITY, height_max: f32::INFINITY };
+    let part_spec = PartSpec {
+        name: "Wing".to_string(),
+        temp: 1500.0,
+        absorbation_const: 0.3,
+        structures: vec![structure("Black_Tiles", 0.25), structure("Plain_Tiles", 0.75)],
+        ..Default::default()
+    };
+    let part = compute_part(&part_spec, &[black, plain], &temp_grid).unwrap();
+    assert!((part.absorbation_const - 0.45).abs() < 1e-6, "{}", part.absorbation_const);
+
+    write_material(&directory, "Black", "Solar Absorptance,1.2,,\n");
+    let invalid = load_tps_spec(&directory.path().join("Black_Tiles.csv"), &library).unwrap();
+    assert!(validate_tps(&invalid).iter().any(|issue| issue.severity == Severity::Error && issue.message.contains("Solar Absorptance")));
+}
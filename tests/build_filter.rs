@@ -0,0 +1,40 @@
+use std::process::{Command, Output};
+
+/// `build` of the shipped library with the filter `args`, the names of the written part presets
+fn build(name: &str, args: &[&str]) -> (Output, Vec<String>) {
+    let out = std::env::temp_dir().join(format!("ro_materials_filter_{}_{}", name, std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_ro_materials_csv_calculator"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("--out").arg(&out).arg("build").args(args)
+        .output()
+        .unwrap();
+    let mut parts = std::fs::read_dir(out.join("Part")).map_or(Vec::new(), |entries| entries
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|file| file.ends_with(".cfg"))
+        .collect());
+    parts.sort();
+    let _ = std::fs::remove_dir_all(&out);
+    (output, parts)
+}
+
+#[test]
+fn tps_alone_keeps_its_parts() {
+    let (output, parts) = build("tps", &["--tps", "Inconel-X"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    // built from Inconel-X only, parts with other structures as well stay out
+    assert_eq!(parts, ["X-15 Wing.cfg"]);
+
+    let (output, parts) = build("tps_part", &["--tps", "Inconel-X", "--part", "X-1 Cockpit"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(parts, ["X-1 Cockpit.cfg", "X-15 Wing.cfg"]);
+}
+
+#[test]
+fn unknown_names() {
+    let (output, _parts) = build("unknown", &["--tps", "Inconel-Y", "--part", "X-1 Cockpit", "--part", "X-3 Cockpit"]);
+    assert!(!output.status.success());
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(report.contains("TPS not found. [\"Inconel-Y\"]"), "{}", report);
+    assert!(report.contains("Part not found. [\"X-3 Cockpit\"]"), "{}", report);
+    assert!(report.contains("2 error(s)"), "{}", report);
+}
@@ -0,0 +1,57 @@
+use ro_materials_csv_calculator::*;
+
+fn read_material(content: &str) -> Segment {
+    let path = std::env::temp_dir().join(format!("ro_materials_citation_{}.csv", std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    let mut segment = Segment { path: path.display().to_string(), ..Default::default() };
+    read_material_csv(&mut segment).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    segment
+}
+
+#[test]
+fn citation_rows() {
+    let segment = read_material("Name,LI-900,,\nSource,NASA TM-2004-212789,,\nReference,https://tpsx.arc.nasa.gov/,,\n\
+        Reference,Williams & Curry, Thermal Protection Materials,\nNotes,\"k above 1200 K extrapolated, not measured\",,\nConfidence,high,,\n\
+        Temperature,Specific Heat,Thermal Conductivity,Emissivity\n300,900,0.05,0.8\n");
+
+    assert_eq!(segment.citation, Citation {
+        source: "NASA TM-2004-212789".to_string(),
+        references: vec!["https://tpsx.arc.nasa.gov/".to_string(), "Williams & Curry, Thermal Protection Materials".to_string()],
+        notes: vec!["k above 1200 K extrapolated, not measured".to_string()],
+        confidence: "high".to_string(),
+    });
+    assert_eq!(segment.citation.lines("LI-900"), [
+        "LI-900: NASA TM-2004-212789 (confidence: high)",
+        "    https://tpsx.arc.nasa.gov/",
+        "    Williams & Curry, Thermal Protection Materials",
+        "    Note: k above 1200 K extrapolated, not measured",
+    ]);
+}
+
+#[test]
+fn tps_comments_list_each_cited_material_once() {
+    let cited = |name: &str, reference: &str| Segment {
+        name: name.to_string(),
+        citation: Citation { references: vec![reference.to_string()], ..Default::default() },
+        ..Default::default()
+    };
+    let uncited = Segment { name: "RTV".to_string(), ..Default::default() };
+    let table = [(1000.0, Data { cp: 1000.0, R_th: 0.1, e: 0.8 })].into_iter().collect::<PropertyTable<Data>>();
+    let tps = TPS {
+        name: "HRSI".to_string(),
+        temp: 1000.0,
+        data_min: table.clone(),
+        data_max: table,
+        segments_min: vec![cited("LI-900", "a"), uncited.clone()],
+        segments_max: vec![cited("LI-900", "a"), uncited, cited("SIP", "b")],
+        ..Default::default()
+    };
+
+    let comments = Preset::from_tps(&tps, ReferenceTemp::Max).comments;
+    let references = comments.iter().position(|line| line == "References:").unwrap();
+    assert_eq!(&comments[references..], ["References:", "LI-900:", "    a", "SIP:", "    b"]);
+
+    let tps = TPS { segments_min: Vec::new(), segments_max: Vec::new(), ..tps };
+    assert!(!Preset::from_tps(&tps, ReferenceTemp::Max).comments.iter().any(|line| line == "References:"));
+}
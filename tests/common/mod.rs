@@ -0,0 +1,88 @@
+//! Fixtures shared by the integration tests, not every test file uses all of them
+#![allow(dead_code)]
+
+use ro_materials_csv_calculator::*;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Table of a material file, good from 300 K to 1500 K
+pub const TABLE: &str = "Temperature,Specific Heat,Thermal Conductivity,Emissivity\n300,900,0.05,0.8\n1500,1300,0.12,0.8\n";
+
+/// Directory in the system temp dir, removed again when dropped, also after a failed assertion
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("ro_materials_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write `content` into the file `name` of the directory
+    pub fn write(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The library in bib/
+pub fn library_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("bib")
+}
+
+pub fn temp_grid() -> TempGrid {
+    TempGrid::new(read_temp_list_csv2(&library_root().join("Temp_List.csv")).unwrap())
+}
+
+/// Layer covering the whole area
+pub fn segment(tickness: f32, density: f32) -> Segment {
+    Segment { portion: 1.0, tickness, density, areal_density: density * tickness, ..Default::default() }
+}
+
+/// Material file `<name>.csv` of density 144 with [`TABLE`], `rows` go into the header
+pub fn write_material(directory: &TempDir, name: &str, rows: &str) -> PathBuf {
+    directory.write(&format!("{}.csv", name), &format!("Name,{},,\nDensity,144,,\n{}{}", name, rows, TABLE))
+}
+
+/// TPS file `<name>.csv` of a single `material` layer, 0.01 m thick in Min & 0.02 m in Max at 1500 K, `rows` go into the header
+pub fn write_tps(directory: &TempDir, name: &str, material: &str, rows: &str) -> PathBuf {
+    directory.write(&format!("{}.csv", name), &format!("Name,{},,,,\nTemperature,1500,,,,\n{}\
+        Min,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\nTop Layer,{},1,0.01,1500,500\n\
+        Max,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\nTop Layer,{},1,0.02,1500,400\n", name, rows, material, material))
+}
+
+/// Calculated TPS with rows from 900 K to 1100 K, twice the cp & insulance in Max
+pub fn tiles(name: &str) -> TPS {
+    let table = |scale: f32| [(900.0, 0.8), (1000.0, 0.85), (1100.0, 0.9)].into_iter()
+        .map(|(temp, e)| (temp, Data { cp: temp * scale, R_th: 0.01 * temp * scale, e }))
+        .collect::<PropertyTable<Data>>();
+    TPS {
+        name: name.to_string(),
+        description: "Tiles on felt".to_string(),
+        temp: 1000.0,
+        tickness_min: 0.0254,
+        tickness_max: 0.0508,
+        areal_density_min: 3.6576,
+        areal_density_max: 7.3152,
+        cost_per_area_min: 120.5,
+        cost_per_area_max: 241.0,
+        absorbation_const: Some(0.85),
+        data_min: table(1.0),
+        data_max: table(2.0),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+use std::path::Path;
+
+fn parse(text: &str) -> Result<ConfigNode, CalcError> {
+    ConfigNode::parse(Path::new("Test.cfg"), text)
+}
+
+#[test]
+fn nested_nodes_and_comments() {
+    let text = "// header comment\nPART\n{\n    name = Wing // trailing comment\n    MODULE { name = ModuleAblator }\n\
+        MODULE\n    {\n        name = ModuleROThermal\n        CURVE\n        {\n            key = 0 1\n        }\n    }\n}\nversion = 2\n";
+    let root = parse(text).unwrap();
+    assert_eq!(root.get("version"), Some("2"));
+    let part = &root.nodes[0];
+    assert_eq!((part.name.as_str(), part.line), ("PART", 3));
+    assert_eq!(part.get("name"), Some("Wing"));
+    assert_eq!(part.nodes.len(), 2);
+    assert_eq!(part.nodes[0].get("name"), Some("ModuleAblator"));
+    assert_eq!(part.nodes[1].nodes[0].get("key"), Some("0 1"));
+
+    let mut curves = Vec::<&ConfigNode>::new();
+    root.find_all("CURVE", &mut curves);
+    assert_eq!(curves.len(), 1);
+}
+
+#[test]
+fn patch_headers() {
+    let text = "@ROThermal_PRESET[HRSI]:NEEDS[ROThermal]:FOR[ROLibrary]\n{\n    @skinMaxTemp = 1600\n    !emissiveConstant = delete\n    emissiveConstant = 0.9\n}\n\
+        %ROThermal_PRESET[LRSI]:NEEDS[ROThermal]\n{\n    %skinMaxTemp = 650\n}\n";
+    let root = parse(text).unwrap();
+    let hrsi = &root.nodes[0];
+    assert_eq!(hrsi.type_name(), "ROThermal_PRESET");
+    assert_eq!(hrsi.get("skinMaxTemp"), Some("1600"));
+    // the deletion doesn't count as value
+    assert_eq!(hrsi.get("emissiveConstant"), Some("0.9"));
+
+    let mut presets = Vec::<&ConfigNode>::new();
+    root.find_all("ROThermal_PRESET", &mut presets);
+    let names = presets.iter().map(|node| PresetData::from_node(node, Path::new("Test.cfg")).unwrap().name).collect::<Vec<String>>();
+    assert_eq!(names, ["HRSI", "LRSI"]);
+    assert_eq!(root.nodes[1].type_name(), "ROThermal_PRESET");
+    assert_eq!(root.nodes[1].get("skinMaxTemp"), Some("650"));
+}
+
+#[test]
+fn syntax_errors() {
+    let line = |text: &str| match parse(text) {
+        Err(CalcError::Config { line, .. }) => line,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(line("NODE\n{\n    a = 1\n}\n}\n"), 5);
+    assert_eq!(line("NODE\n    a = 1\n"), 1);
+    assert_eq!(line("NODE\n{\n    INNER\n    {\n}\n"), 2);
+    assert_eq!(line("{\n}\n"), 1);
+}
+
+#[test]
+fn preset_round_trip() {
+    let directory = TempDir::new("config_node");
+    for format in [PresetFormat::default(), PresetFormat { patch: Some(PatchOp::Edit), ..Default::default() }] {
+        let mut presets = vec![Preset::from_tps(&tiles("Tiles_B"), ReferenceTemp::Max), Preset::from_tps(&tiles("Tiles_A"), ReferenceTemp::Max)];
+        let expected = presets.iter()
+            .map(|preset| PresetData::from_node(&preset.to_node(), Path::new("Tiles.cfg")).unwrap())
+            .collect::<Vec<PresetData>>();
+        let path = directory.path().join("Tiles.cfg");
+        output_presets(&mut presets, &path, &format).unwrap();
+
+        let read = read_preset_cfg(&path).unwrap();
+        // sorted by name
+        assert_eq!(read.iter().map(|data| data.name.as_str()).collect::<Vec<&str>>(), ["Tiles_A", "Tiles_B"]);
+        let data = &read[0];
+        assert_eq!(data.description, "Tiles on felt");
+        assert_eq!(data.temp, Some(1000.0));
+        assert_eq!((data.tickness_min, data.tickness_max), (Some(0.0254), Some(0.0508)));
+        assert_eq!((data.areal_density_min, data.areal_density_max), (Some(3.6576), Some(7.3152)));
+        assert_eq!((data.cost_per_area, data.cost_per_area_max), (Some(120.5), Some(241.0)));
+        assert_eq!(data.absorbation_const, Some(0.85));
+        assert_eq!(data.has_ablator, Some(false));
+        assert_eq!((data.emissive_const, data.cp_min, data.insulance_max), (Some(0.85), Some(1000.0), Some(20.0)));
+        // every value written comes back
+        let debug = |data: &PresetData| format!("{:?}", PresetData { path: Default::default(), ..data.clone() });
+        assert_eq!(debug(data), debug(&expected[1]));
+        assert_eq!(debug(&read[1]), debug(&expected[0]));
+    }
+}
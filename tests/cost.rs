@@ -0,0 +1,94 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+#[test]
+fn cost_basis() {
+    assert_eq!(CostBasis::parse("/kg"), Some(CostBasis::Mass));
+    assert_eq!(CostBasis::parse("kg"), Some(CostBasis::Mass));
+    assert_eq!(CostBasis::parse("/m²·mm"), Some(CostBasis::AreaThickness));
+    assert_eq!(CostBasis::parse("/m^2 mm"), Some(CostBasis::AreaThickness));
+    assert_eq!(CostBasis::parse("/m²"), None);
+
+    let layer = Segment { portion: 0.5, ..segment(0.02, 150.0) };
+    assert!((Cost { value: 40.0, basis: CostBasis::Mass }.per_area(&layer) - 60.0).abs() < 1e-4);
+    assert!((Cost { value: 12.0, basis: CostBasis::AreaThickness }.per_area(&layer) - 120.0).abs() < 1e-4);
+}
+
+#[test]
+fn tps_and_part_cost() {
+    let tile = Some(Cost { value: 40.0, basis: CostBasis::Mass });
+    let mut tps = TPS {
+        manufacturing_factor: 2.0,
+        segments_min: vec![Segment { cost: tile, ..segment(0.01, 150.0) }, segment(0.002, 2700.0)],
+        segments_max: vec![Segment { cost: tile, ..segment(0.03, 150.0) }, segment(0.002, 2700.0)],
+        ..Default::default()
+    };
+    calc_tps_height_density(&mut tps);
+    assert!((tps.cost_per_area_min - 120.0).abs() < 1e-3);
+    assert!((tps.cost_per_area_max - 360.0).abs() < 1e-3);
+
+    // halfway between the thicknesses
+    let resized = tps_change_height(&tps, 0.022, f32::INFINITY);
+    assert!((resized.cost_per_area_min - 240.0).abs() < 1e-2, "{}", resized.cost_per_area_min);
+    assert_eq!(resized.cost_per_area_max, tps.cost_per_area_max);
+    let thinnest = tps_change_height(&tps, f32::NEG_INFINITY, f32::NEG_INFINITY);
+    assert_eq!(thinnest.cost_per_area_max, tps.cost_per_area_min);
+
+    let mut part = Part {
+        name: String::new(),
+        description: String::new(),
+        temp: 0.0,
+        absorbation_const: 0.0,
+        cost_per_area: 10.0,
+        cost_per_area_min: 0.0,
+        cost_per_area_max: 0.0,
+        has_ablator: false,
+        height_min: 0.0,
+        height_max: 0.0,
+        areal_density_min: 0.0,
+        areal_density_max: 0.0,
+        tps_list: vec![(tps.clone(), 0.25, PropertyTable::default(), PropertyTable::default()),
+                       (TPS { cost_per_area_min: 0.0, cost_per_area_max: 0.0, ..tps }, 0.75, PropertyTable::default(), PropertyTable::default())],
+        data_min: PropertyTable::default(),
+        data_max: PropertyTable::default(),
+        ablator: None,
+        info: None,
+    };
+    // the priced TPS replace the CostPerArea row
+    calculate_part(&mut part, &[]).unwrap();
+    assert!((part.cost_per_area_min - 30.0).abs() < 1e-3);
+    assert!((part.cost_per_area_max - 90.0).abs() < 1e-3);
+
+    // without prices it stands in
+    part.tps_list.remove(0);
+    calculate_part(&mut part, &[]).unwrap();
+    assert_eq!((part.cost_per_area_min, part.cost_per_area_max), (10.0, 10.0));
+}
+
+#[test]
+fn cost_rows() {
+    let library = MaterialLibrary::new(&library_root());
+    let directory = TempDir::new("cost");
+    write_material(&directory, "Tile", "Cost,12,/m²·mm\n");
+    let tps_path = write_tps(&directory, "Tiles", "Tile.csv", "Manufacturing Factor,2.5,,,,\n");
+
+    let spec = load_tps_spec(&tps_path, &library).unwrap();
+    assert_eq!(spec.manufacturing_factor, Some(2.5));
+    assert_eq!(spec.segments_min[0].cost, Some(Cost { value: 12.0, basis: CostBasis::AreaThickness }));
+    assert!(validate_tps(&spec).iter().all(|issue| issue.severity == Severity::Warning), "{:?}", validate_tps(&spec));
+
+    let tps = compute_tps(&spec, &temp_grid());
+    let node = Preset::from_tps(&tps, ReferenceTemp::Max).to_node();
+    let value = |key: &str| node.get(key).unwrap().parse::<f32>().unwrap();
+    assert!((value("costPerArea") - 300.0).abs() < 1e-3);
+    assert!((value("costPerAreaMax") - 600.0).abs() < 1e-3);
+
+    // the factor survives a round trip
+    let written = output_tps_csv(&spec, &directory.path().join("written")).unwrap();
+    assert_eq!(read_tps_csv(&written).unwrap().manufacturing_factor, Some(2.5));
+
+    write_material(&directory, "Tile", "Cost,12,/m²\n");
+    assert!(load_tps_spec(&tps_path, &library).is_err());
+}
@@ -0,0 +1,58 @@
+use ro_materials_csv_calculator::*;
+
+use std::path::Path;
+
+fn tps() -> TPS {
+    let table = |scale: f32| [(900.0, 0.8), (1000.0, 0.85), (1100.0, 0.9)].into_iter()
+        .map(|(temp, e)| (temp, Data { cp: temp * scale, R_th: 0.01 * temp * scale, e }))
+        .collect::<PropertyTable<Data>>();
+    TPS { name: "Tiles".to_string(), temp: 1000.0, data_min: table(1.0), data_max: table(2.0), ..Default::default() }
+}
+
+fn read_back(preset: &Preset, format: &PresetFormat) -> (String, PresetData) {
+    let mut text = Vec::<u8>::new();
+    preset.write(&mut text, format).unwrap();
+    let text = String::from_utf8(text).unwrap();
+    let root = ConfigNode::parse(Path::new("Tiles.cfg"), &text).unwrap();
+    let data = PresetData::from_node(&root.nodes[0], Path::new("Tiles.cfg")).unwrap();
+    (text, data)
+}
+
+#[test]
+fn float_curves() {
+    let preset = Preset::from_tps(&tps(), ReferenceTemp::Max);
+    let (scalar_text, scalar) = read_back(&preset, &PresetFormat::default());
+    assert!(!scalar_text.contains("key ="));
+
+    let (text, curves) = read_back(&preset, &PresetFormat { curves: true, ..Default::default() });
+    assert!(!text.contains("emissiveConstant ="));
+    assert!(text.contains("    thermalInsulanceMax\n    {\n        key = 900 18\n        key = 1000 20\n        key = 1100 22\n    }\n"), "{}", text);
+
+    // read at skinMaxTemp, the curves give the scalar values back
+    assert_eq!(curves.emissive_const, scalar.emissive_const);
+    assert_eq!(curves.cp_min, scalar.cp_min);
+    assert_eq!(curves.insulance_min, scalar.insulance_min);
+    assert_eq!(curves.cp_max, scalar.cp_max);
+    assert_eq!(curves.insulance_max, scalar.insulance_max);
+    assert_eq!(curves.areal_density_min, scalar.areal_density_min);
+}
+
+#[test]
+fn float_curve_patch() {
+    let format = PresetFormat { patch: Some(PatchOp::Edit), curves: true, ..Default::default() };
+    let (text, data) = read_back(&Preset::from_tps(&tps(), ReferenceTemp::Max), &format);
+    assert!(text.contains("    !emissiveConstant = delete\n    !emissiveConstant {}\n    emissiveConstant\n    {\n"), "{}", text);
+    assert_eq!(data.emissive_const, Some(0.85));
+}
+
+#[test]
+fn float_curve_between_keys() {
+    let text = "ROThermal_PRESET\n{\n    name = Tiles\n    skinMaxTemp = 950\n    emissiveConstant\n    {\n        key = 900 0.8\n        key = 1000 0.9\n    }\n}\n";
+    let root = ConfigNode::parse(Path::new("Tiles.cfg"), text).unwrap();
+    let data = PresetData::from_node(&root.nodes[0], Path::new("Tiles.cfg")).unwrap();
+    assert!((data.emissive_const.unwrap() - 0.85).abs() < 1e-6);
+
+    let broken = text.replace("key = 1000 0.9", "key = 1000");
+    let root = ConfigNode::parse(Path::new("Tiles.cfg"), &broken).unwrap();
+    assert!(PresetData::from_node(&root.nodes[0], Path::new("Tiles.cfg")).is_err());
+}
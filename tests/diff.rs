@@ -0,0 +1,169 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+use ro_materials_csv_calculator::diff::canonicalize_missing;
+
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+};
+
+const TOLERANCE: Tolerance = Tolerance { preset: 0.001, curve: 0.01 };
+
+fn write_tree(path: &Path, tps_list: &[TPS]) {
+    for tps in tps_list {
+        output_tps(tps, path, &PresetFormat::default()).unwrap();
+    }
+}
+
+#[test]
+fn unchanged_and_within_tolerance() {
+    let directory = TempDir::new("diff_tolerance");
+    let (old, new) = (directory.path().join("old"), directory.path().join("new"));
+    write_tree(&old, &[tiles("Tiles_A"), tiles("Tiles_B")]);
+    // 0.01 % heavier, below both tolerances
+    write_tree(&new, &[tiles("Tiles_A"), TPS { areal_density_min: 3.6576 * 1.0001, ..tiles("Tiles_B") }]);
+
+    let diff = diff_outputs(&old, &new, TOLERANCE).unwrap();
+    assert!(diff.changed.is_empty() && diff.added.is_empty() && diff.removed.is_empty(), "{:?}", diff);
+    assert!(!diff.is_regression());
+    // without tolerance it shows
+    let diff = diff_outputs(&old, &new, Tolerance { preset: 0.0, curve: 0.0 }).unwrap();
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].values[0].key, "skinMassPerArea");
+}
+
+#[test]
+fn changed_presets() {
+    let directory = TempDir::new("diff_changed");
+    let (old, new) = (directory.path().join("old"), directory.path().join("new"));
+    write_tree(&old, &[tiles("Tiles_A"), tiles("Tiles_B")]);
+    let mut cold_change = tiles("Tiles_B");
+    // only the 900 K row, the preset values at 1000 K stay
+    cold_change.data_min.values_mut()[0].cp = 990.0;
+    write_tree(&new, &[TPS { areal_density_max: 8.0, ..tiles("Tiles_A") }, cold_change]);
+
+    let diff = diff_outputs(&old, &new, TOLERANCE).unwrap();
+    assert!(diff.is_regression());
+    let names = diff.changed.iter().map(|preset| preset.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names, ["Tiles_A", "Tiles_B"]);
+
+    let tiles_a = &diff.changed[0];
+    assert_eq!(tiles_a.values.len(), 1);
+    assert_eq!((tiles_a.values[0].key, tiles_a.values[0].old, tiles_a.values[0].new), ("skinMassPerAreaMax", Some(7.3152), Some(8.0)));
+    assert!(tiles_a.curves.is_empty());
+
+    let tiles_b = &diff.changed[1];
+    assert!(tiles_b.values.is_empty());
+    assert_eq!(tiles_b.curves.len(), 1);
+    let curve = &tiles_b.curves[0];
+    assert_eq!((curve.file.as_str(), curve.column, curve.temp, curve.old, curve.new), ("Tiles_B_min.csv", "Heat Capacity", 900.0, 900.0, 990.0));
+}
+
+#[test]
+fn added_and_removed_presets() {
+    let directory = TempDir::new("diff_added");
+    let (old, new) = (directory.path().join("old"), directory.path().join("new"));
+    write_tree(&old, &[tiles("Tiles_A"), tiles("Tiles_Old")]);
+    write_tree(&new, &[tiles("Tiles_A"), tiles("Tiles_New")]);
+
+    let diff = diff_outputs(&old, &new, TOLERANCE).unwrap();
+    assert!(diff.changed.is_empty());
+    assert_eq!(diff.added, ["Tiles_New"]);
+    assert_eq!(diff.removed, ["Tiles_Old"]);
+    assert!(diff.is_regression());
+
+    // only added presets count too
+    let diff = diff_outputs(&old, &old, TOLERANCE).unwrap();
+    assert!(!diff.is_regression());
+    let empty = directory.path().join("empty");
+    let diff = diff_outputs(&empty, &new, TOLERANCE).unwrap();
+    assert_eq!(diff.added, ["Tiles_A", "Tiles_New"]);
+    assert!(diff.is_regression());
+}
+
+#[test]
+fn non_finite_values_exceed_tolerance() {
+    let directory = TempDir::new("diff_non_finite");
+    let (old, new) = (directory.path().join("old"), directory.path().join("new"));
+    let mut broken = tiles("Tiles_B");
+    // written as 1 / R_th, an infinite insulance
+    broken.data_max.values_mut()[2].R_th = 0.0;
+    write_tree(&old, &[tiles("Tiles_A"), broken.clone()]);
+    let mut nan_curve = tiles("Tiles_A");
+    nan_curve.data_min.values_mut()[0].cp = f32::NAN;
+    write_tree(&new, &[TPS { areal_density_min: f32::NAN, ..nan_curve }, broken]);
+
+    let diff = diff_outputs(&old, &new, Tolerance { preset: f32::MAX, curve: f32::MAX }).unwrap();
+    let names = diff.changed.iter().map(|preset| preset.name.as_str()).collect::<Vec<&str>>();
+    // unchanged but still infinite in both trees
+    assert_eq!(names, ["Tiles_A", "Tiles_B"]);
+
+    let tiles_a = &diff.changed[0];
+    assert_eq!(tiles_a.values.len(), 1);
+    assert_eq!(tiles_a.values[0].key, "skinMassPerArea");
+    assert!(tiles_a.values[0].new.unwrap().is_nan());
+    assert_eq!((tiles_a.curves[0].file.as_str(), tiles_a.curves[0].temp), ("Tiles_A_min.csv", 900.0));
+    assert!(tiles_a.curves[0].new.is_nan());
+
+    let tiles_b = &diff.changed[1];
+    assert!(tiles_b.values.is_empty());
+    assert_eq!((tiles_b.curves[0].file.as_str(), tiles_b.curves[0].column, tiles_b.curves[0].temp), ("Tiles_B_max.csv", "Thermal Insulance", 1100.0));
+    assert!(diff.is_regression());
+}
+
+#[test]
+fn report_lines() {
+    let directory = TempDir::new("diff_report");
+    let (old, new) = (directory.path().join("old"), directory.path().join("new"));
+    write_tree(&old, &[tiles("Tiles_A"), tiles("Tiles_Old")]);
+    write_tree(&new, &[TPS { areal_density_max: 8.0, ..tiles("Tiles_A") }, tiles("Tiles_New")]);
+
+    let diff = diff_against(&Baseline::Directory(old), &new, &directory.path().join("scratch"), TOLERANCE).unwrap();
+    assert_eq!(diff.to_string(), "Added Tiles_New\nRemoved Tiles_Old\nTiles_A: skinMassPerAreaMax 7.3152 -> 8 (+9.36%)\n1 changed, 1 added, 1 removed");
+}
+
+#[test]
+fn against_revision() {
+    let directory = TempDir::new("diff_revision");
+    let git = |args: &[&str]| {
+        let output = Command::new("git").arg("-C").arg(directory.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false"]).args(args)
+            .output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    };
+    let out = directory.path().join("out");
+    git(&["init", "-q"]);
+    write_tree(&out, &[tiles("Tiles_A")]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "presets"]);
+    // changed after the commit
+    fs::remove_dir_all(&out).unwrap();
+    write_tree(&out, &[TPS { areal_density_max: 8.0, ..tiles("Tiles_A") }]);
+
+    let scratch = directory.path().join("scratch");
+    checkout_revision("HEAD", &out, &scratch).unwrap();
+    assert!(scratch.join("TPS/Tiles_A.cfg").is_file() && scratch.join("csv/Tiles_A_min.csv").is_file());
+    let diff = diff_outputs(&scratch, &out, TOLERANCE).unwrap();
+    assert_eq!(diff.changed[0].values[0].old, Some(7.3152));
+    fs::remove_dir_all(&scratch).unwrap();
+
+    let baseline = Baseline::Revision { rev: "HEAD".to_string(), directory: out.clone() };
+    let diff = diff_against(&baseline, &out, &scratch, TOLERANCE).unwrap();
+    assert_eq!(diff.changed.len(), 1);
+    assert!(checkout_revision("HEAD", &directory.path().join("elsewhere"), &scratch).is_err());
+    assert!(checkout_revision("no-such-rev", &out, &scratch).is_err());
+}
+
+#[test]
+fn canonical_missing_paths() {
+    let directory = TempDir::new("diff_canonical");
+    let canonical = fs::canonicalize(directory.path()).unwrap();
+    fs::create_dir(directory.path().join("a")).unwrap();
+    // the existing part gets resolved, the missing one appended
+    assert_eq!(canonicalize_missing(&directory.path().join("a/../b/c")).unwrap(), canonical.join("b/c"));
+    assert_eq!(canonicalize_missing(directory.path()).unwrap(), canonical);
+}
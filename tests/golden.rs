@@ -0,0 +1,89 @@
+//! Builds every TPS & part of bib/ and compares the presets & csv curves with tests/golden.
+//!
+//! After an intended change of the results update the golden files with
+//! `cargo test --test golden -- --bless`
+
+use ro_materials_csv_calculator::*;
+
+use std::{
+    env,
+    fs,
+    path::Path,
+    process,
+};
+
+/// Relative deviation still accepted, float sums may differ between platforms
+const TOLERANCE: Tolerance = Tolerance { preset: 1e-4, curve: 1e-4 };
+
+fn main() {
+    let bless = env::args().any(|arg| arg == "--bless");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let golden = root.join("tests").join("golden");
+    let out = env::temp_dir().join(format!("ro_materials_golden_{}", process::id()));
+
+    build_library(&root.join("bib"), &out);
+
+    if bless {
+        for directory in ["TPS", "Part", "csv"] {
+            let _ = fs::remove_dir_all(golden.join(directory));
+            copy_dir(&out.join(directory), &golden.join(directory));
+        }
+        fs::remove_dir_all(&out).unwrap();
+        println!("blessed {}", golden.display());
+        return;
+    }
+
+    let diff = diff_outputs(&golden, &out, TOLERANCE).unwrap();
+    fs::remove_dir_all(&out).unwrap();
+
+    for name in diff.added.iter() {
+        println!("{}: no golden file, run with --bless to add it", name);
+    }
+    for name in diff.removed.iter() {
+        println!("{}: no longer built", name);
+    }
+    for preset in diff.changed.iter() {
+        for change in preset.values.iter() {
+            println!("{}: {}", preset.name, change);
+        }
+        for change in preset.curves.iter() {
+            println!("{}: {}", preset.name, change);
+        }
+    }
+    if diff.is_regression() {
+        println!("golden test failed, run `cargo test --test golden -- --bless` if the change is intended");
+        process::exit(1);
+    }
+    println!("golden test ok");
+}
+
+/// Same steps as the build command of the binary
+fn build_library(library_root: &Path, out: &Path) {
+    let temp_grid = TempGrid::new(read_temp_list_csv2(&library_root.join("Temp_List.csv")).unwrap());
+    let library = MaterialLibrary::new(library_root);
+
+    let mut tps_list = Vec::<TPS>::new();
+    for path in get_files(&library_root.join("tps"), "csv").unwrap() {
+        let spec = load_tps_spec(&path, &library).unwrap();
+        let errors = validate_tps(&spec).into_iter().filter(|issue| issue.severity == Severity::Error).collect::<Vec<Issue>>();
+        assert!(errors.is_empty(), "{}: {:?}", path.display(), errors);
+
+        let tps = compute_tps(&spec, &temp_grid);
+        output_tps(&tps, out, &PresetFormat::default()).unwrap();
+        tps_list.push(tps);
+    }
+
+    for path in get_files(&library_root.join("part"), "csv").unwrap() {
+        let spec = read_part_csv(&path).unwrap();
+        let part = compute_part(&spec, &tps_list, &temp_grid).unwrap();
+        output_part(part, out, &PresetFormat::default()).unwrap();
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        fs::copy(&path, to.join(path.file_name().unwrap())).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use ro_materials_csv_calculator::*;
+
+fn layer_rows(rows: &[(f32, f32)]) -> PropertyTable<LayerData> {
+    rows.iter()
+        .map(|(temp, cp)| (*temp, LayerData { thermal_data: Data { cp: *cp, R_th: *cp, e: 0.8 }, temp_sub_part: *temp }))
+        .collect()
+}
+
+fn data_rows(rows: &[(f32, f32)]) -> PropertyTable<Data> {
+    rows.iter().map(|(temp, cp)| (*temp, Data { cp: *cp, R_th: *cp, e: 0.8 })).collect()
+}
+
+#[test]
+fn tps_data_between_layer_rows() {
+    // layers with rows every 200 K summed on a 100 K grid
+    let segments = [
+        Segment { data_tps_temp_mult: layer_rows(&[(300.0, 1.0), (500.0, 3.0)]), ..Default::default() },
+        Segment { data_tps_temp_mult: layer_rows(&[(300.0, 10.0), (400.0, 10.0), (500.0, 10.0)]), ..Default::default() },
+    ];
+    let data = calc_tps_data(&segments, &[300.0, 400.0, 500.0]);
+    assert_eq!(data.temps(), &[300.0, 400.0, 500.0]);
+    assert_eq!(data.at(400.0).unwrap(), Data { cp: 12.0, R_th: 12.0, e: 0.8 });
+}
+
+#[test]
+fn change_height_between_rows() {
+    let tps = TPS {
+        tickness_min: 0.01,
+        tickness_max: 0.03,
+        data_min: data_rows(&[(300.0, 1.0), (400.0, 1.0), (500.0, 1.0)]),
+        data_max: data_rows(&[(300.0, 3.0), (500.0, 5.0)]),
+        ..Default::default()
+    };
+    let resized = tps_change_height(&tps, 0.02, f32::INFINITY);
+    assert_eq!(resized.data_min.temps(), &[300.0, 400.0, 500.0]);
+    // halfway to the 4.0 the max table has at 400 K
+    assert_eq!(resized.data_min.at(400.0).unwrap().cp, 2.5);
+}
+
+#[test]
+fn part_between_tps_rows() {
+    let tps = TPS { areal_density_min: 5.0, areal_density_max: 5.0, ..Default::default() };
+    let mut part = Part {
+        name: String::new(),
+        description: String::new(),
+        temp: 0.0,
+        absorbation_const: 0.0,
+        cost_per_area: 0.0,
+        cost_per_area_min: 0.0,
+        cost_per_area_max: 0.0,
+        has_ablator: false,
+        height_min: 0.0,
+        height_max: 0.0,
+        areal_density_min: 0.0,
+        areal_density_max: 0.0,
+        tps_list: vec![(tps, 1.0, layer_rows(&[(300.0, 1.0), (500.0, 3.0)]), layer_rows(&[(300.0, 2.0), (500.0, 2.0)]))],
+        data_min: PropertyTable::default(),
+        data_max: PropertyTable::default(),
+        ablator: None,
+        info: None,
+    };
+    calculate_part(&mut part, &[300.0, 400.0, 500.0]).unwrap();
+    assert_eq!(part.data_min.temps(), &[275.0, 375.0, 475.0]);
+    assert_eq!(part.data_min.values()[1], Data { cp: 2.0, R_th: 0.5, e: 0.8 });
+    assert_eq!(part.data_max.values()[1], Data { cp: 2.0, R_th: 0.5, e: 0.8 });
+}
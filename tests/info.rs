@@ -0,0 +1,74 @@
+use ro_materials_csv_calculator::*;
+
+use std::{fs, path::Path};
+
+#[test]
+fn parse_sidecar() {
+    let text = "# comment\nVehicle: Space Shuttle\nAuthor: someone\n\nReference: [1] https://ntrs.nasa.gov/citations/19850008628\n\
+        Reference: [2] Press Information\nValidation: matches STS-5 flight data\nNotes: Nose: Dynaflex blankets\nApplies To: RCC_Nose\n";
+    let info = Info::parse(Path::new("tps/RCC.info"), text).unwrap();
+
+    assert_eq!(info.vehicle, "Space Shuttle");
+    assert_eq!(info.author, "someone");
+    assert_eq!(info.references, ["[1] https://ntrs.nasa.gov/citations/19850008628", "[2] Press Information"]);
+    assert_eq!(info.notes, ["Nose: Dynaflex blankets"]);
+    assert!(info.applies_to(Path::new("tps/RCC.csv")));
+    assert!(info.applies_to(Path::new("tps/RCC_Nose.csv")));
+    assert!(!info.applies_to(Path::new("tps/LRSI.csv")));
+    assert_eq!(info.lines(), [
+        "Vehicle: Space Shuttle",
+        "Author: someone",
+        "Reference: [1] https://ntrs.nasa.gov/citations/19850008628",
+        "Reference: [2] Press Information",
+        "Validation: matches STS-5 flight data",
+        "Note: Nose: Dynaflex blankets",
+    ]);
+}
+
+#[test]
+fn sidecar_errors() {
+    let path = Path::new("X-15.info");
+    assert!(matches!(Info::parse(path, "Vehicle: X-15\nhot structure"), Err(CalcError::Config { line: 2, .. })));
+    let err = Info::parse(path, "\nVehicel: X-15").unwrap_err();
+    assert_eq!(err.to_string(), "X-15.info:2: unknown key \"Vehicel\"");
+}
+
+#[test]
+fn find_sidecars() {
+    let directory = std::env::temp_dir().join(format!("ro_materials_info_{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("X-15 Wing.info"), "Vehicle: own\n").unwrap();
+    fs::write(directory.join("X-15.info"), "Vehicle: shared\nApplies To: X-15 Cockpit\nApplies To: X-15 Wing\n").unwrap();
+
+    // a broken sidecar of another file doesn't stop the search, only warns
+    fs::write(directory.join("Broken.info"), "Vehicle: X-15\nhot structure\n").unwrap();
+
+    let vehicle = |name: &str| find_info(&directory.join(name)).unwrap().map(|info| info.vehicle);
+    assert_eq!(vehicle("X-15 Wing.csv").as_deref(), Some("own"));
+    assert_eq!(vehicle("X-15 Cockpit.csv").as_deref(), Some("shared"));
+    assert_eq!(vehicle("X-1 Cockpit.csv"), None);
+    assert!(find_info(&directory.join("Broken.csv")).is_err());
+
+    let issues = check_info_files(&directory).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!((issues[0].severity, issues[0].line), (Severity::Warning, Some(2)));
+    // with a csv of its own, the csv fails instead
+    fs::write(directory.join("Broken.csv"), "").unwrap();
+    assert!(check_info_files(&directory).unwrap().is_empty());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn index_document() {
+    let info = Info { vehicle: "North American X-15".to_string(), references: vec!["ref".to_string()], ..Default::default() };
+    let entries = [
+        IndexEntry { kind: "Part", name: "X-15 Wing".to_string(), description: "Inconel X skin".to_string(), info: Some(info) },
+        IndexEntry { kind: "TPS", name: "LRSI".to_string(), description: String::new(), info: None },
+    ];
+    let directory = std::env::temp_dir().join(format!("ro_materials_index_{}", std::process::id()));
+    output_index(&entries, &directory).unwrap();
+
+    let text = fs::read_to_string(directory.join("Index.md")).unwrap();
+    assert_eq!(text, "# Index\n\n## TPS\n\n### LRSI\n\n## Parts\n\n### X-15 Wing\n\nInconel X skin\n\n- Vehicle: North American X-15\n- Reference: ref\n");
+    fs::remove_dir_all(&directory).unwrap();
+}
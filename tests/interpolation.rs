@@ -0,0 +1,220 @@
+use ro_materials_csv_calculator::*;
+use ro_materials_csv_calculator::interpolation::*;
+
+use proptest::prelude::*;
+
+/// Ascending temperatures with non-decreasing, positive values
+fn monotone_rows(max_rows: usize) -> impl Strategy<Value = PropertyTable<LayerData>> {
+    prop::collection::vec((1.0f32..100.0, 0.0f32..50.0, 0.0f32..1.0, 0.0f32..0.1), 1..max_rows)
+        .prop_map(|steps| {
+            let (mut temp, mut cp, mut r_th, mut e) = (100.0, 500.0, 0.1, 0.1);
+            steps.into_iter().map(|(temp_step, cp_step, r_th_step, e_step)| {
+                temp += temp_step;
+                cp += cp_step;
+                r_th += r_th_step;
+                e += e_step;
+                (temp, LayerData { thermal_data: Data { cp, R_th: r_th, e }, temp_sub_part: temp })
+            }).collect()
+        })
+}
+
+fn grid(start: f32, stop: f32, step: f32) -> Vec<f32> {
+    TempGrid::from_range(start, stop, step).temps
+}
+
+fn is_non_decreasing(values: &[f32]) -> bool {
+    values.windows(2).all(|pair| pair[0] <= pair[1] * (1.0 + 1e-6))
+}
+
+proptest! {
+    #[test]
+    fn fit_list_keeps_monotone_data_monotone(rows in monotone_rows(20), step in 1.0f32..60.0) {
+        let fitted = fit_list(&rows, Interpolation::default(), &grid(0.0, 3000.0, step));
+
+        let columns: [fn(&LayerData) -> f32; 3] = [|row| row.thermal_data.cp, |row| row.thermal_data.R_th, |row| row.thermal_data.e];
+        for column in columns {
+            prop_assert!(is_non_decreasing(&fitted.values().iter().map(column).collect::<Vec<f32>>()));
+        }
+    }
+
+    #[test]
+    fn fit_list_returns_grid_points_unchanged(rows in monotone_rows(20)) {
+        let mut temps = rows.temps().to_vec();
+        temps.insert(0, 50.0);
+        temps.push(5000.0);
+        let fitted = fit_list(&rows, Interpolation::default(), &temps);
+
+        prop_assert_eq!(fitted.len(), temps.len());
+        prop_assert_eq!(&fitted.temps()[1..=rows.len()], rows.temps());
+        for (row, fitted_row) in rows.values().iter().zip(fitted.values()[1..].iter()) {
+            prop_assert_eq!(fitted_row.thermal_data.cp, row.thermal_data.cp);
+            prop_assert_eq!(fitted_row.thermal_data.R_th, row.thermal_data.R_th);
+            prop_assert_eq!(fitted_row.thermal_data.e, row.thermal_data.e);
+            prop_assert_eq!(fitted_row.temp_sub_part, row.temp_sub_part);
+        }
+    }
+
+    #[test]
+    fn fill_gaps_keeps_known_values_and_stays_monotone(values in prop::collection::vec((1.0f32..100.0, any::<bool>()), 1..30)) {
+        let temps = (0..values.len()).map(|i| 100.0 + 50.0 * i as f32).collect::<Vec<f32>>();
+        let mut sum = 0.0;
+        let known = values.iter().map(|(step, is_known)| {
+            sum += step;
+            if *is_known { sum } else { 0.0 }
+        }).collect::<Vec<f32>>();
+
+        let mut filled = known.clone();
+        fill_gaps(&temps, &mut filled);
+
+        for (value, filled_value) in known.iter().zip(filled.iter()) {
+            if *value != 0.0 {
+                prop_assert_eq!(value, filled_value);
+            }
+        }
+        prop_assert!(is_non_decreasing(&filled));
+        if known.iter().any(|value| *value != 0.0) {
+            prop_assert!(filled.iter().all(|value| *value != 0.0));
+        }
+    }
+
+    #[test]
+    fn monotone_cubic_keeps_monotone_data_monotone(rows in monotone_rows(20), step in 1.0f32..60.0) {
+        let interpolation = Interpolation::all(Scheme::MonotoneCubic);
+        // inside the rows only, above them fit_list extrapolates
+        let temps = grid(0.0, 3000.0, step).into_iter()
+            .filter(|temp| *temp >= rows.temps()[0] && *temp <= rows.temps()[rows.len() - 1])
+            .collect::<Vec<f32>>();
+        let fitted = fit_list(&rows, interpolation, &temps);
+
+        let columns: [fn(&LayerData) -> f32; 3] = [|row| row.thermal_data.cp, |row| row.thermal_data.R_th, |row| row.thermal_data.e];
+        for column in columns {
+            let values = fitted.values().iter().map(column).collect::<Vec<f32>>();
+            let rows = rows.values().iter().map(column).collect::<Vec<f32>>();
+            prop_assert!(is_non_decreasing(&values));
+            prop_assert!(values.iter().all(|value| *value >= rows[0] * (1.0 - 1e-6) && *value <= rows[rows.len() - 1] * (1.0 + 1e-6)));
+        }
+    }
+
+    #[test]
+    fn schemes_pass_through_the_rows(rows in monotone_rows(20)) {
+        let cp = rows.values().iter().map(|row| row.thermal_data.cp).collect::<Vec<f32>>();
+        for scheme in [Scheme::Linear, Scheme::MonotoneCubic, Scheme::LogLinear] {
+            for (temp, value) in rows.temps().iter().zip(cp.iter()) {
+                prop_assert_eq!(scheme.interpolate(rows.temps(), &cp, *temp), Some(*value));
+            }
+        }
+    }
+
+    #[test]
+    fn locate_agrees_with_interpolate(rows in monotone_rows(20), temp in 0.0f32..3000.0) {
+        let cp = rows.values().iter().map(|row| row.thermal_data.cp).collect::<Vec<f32>>();
+        let value = interpolate(rows.temps(), &cp, temp).unwrap();
+
+        prop_assert!(value >= cp[0] && value <= cp[cp.len() - 1]);
+        prop_assert_eq!(value, rows.at(temp).unwrap().thermal_data.cp);
+        if let Position::At(i) = locate(rows.temps(), temp) {
+            prop_assert_eq!(value, cp[i]);
+        }
+    }
+}
+
+#[test]
+fn single_row_material() {
+    let mut material = [(300.0, Data { cp: 900.0, R_th: 0.0, e: 0.8 })].into_iter().collect::<PropertyTable<Data>>();
+    fill_gaps_in_csv(&mut material, Interpolation::default());
+    assert_eq!(material.values()[0].R_th, 0.0);
+
+    let rows = material.map(|temp, data| LayerData { thermal_data: *data, temp_sub_part: temp });
+    let fitted = fit_list(&rows, Interpolation::default(), &grid(100.0, 1000.0, 50.0));
+    assert_eq!(fitted.len(), 19);
+    assert!(fitted.values().iter().all(|row| row.thermal_data.cp == 900.0 && row.thermal_data.e == 0.8));
+}
+
+#[test]
+fn empty_column_stays_empty() {
+    let mut material = (0..5)
+        .map(|i| (100.0 * i as f32, Data { cp: 900.0 + i as f32, R_th: 0.0, e: 0.0 }))
+        .collect::<PropertyTable<Data>>();
+    material.values_mut()[2].cp = 0.0;
+    fill_gaps_in_csv(&mut material, Interpolation::default());
+
+    assert_eq!(material.values()[2].cp, 902.0);
+    assert!(material.values().iter().all(|row| row.R_th == 0.0 && row.e == 0.0));
+}
+
+#[test]
+fn log_linear_is_exact_for_exponential_data() {
+    let temps = [300.0, 700.0, 1100.0, 1500.0];
+    let k = temps.map(|temp: f32| 0.02 * (temp / 400.0).exp());
+
+    for temp in [400.0, 950.0, 1400.0] {
+        let expected = 0.02 * (temp / 400.0f32).exp();
+        let log_linear = Scheme::LogLinear.interpolate(&temps, &k, temp).unwrap();
+        let linear = Scheme::Linear.interpolate(&temps, &k, temp).unwrap();
+        assert!((log_linear - expected).abs() / expected < 1e-5);
+        assert!(linear > expected);
+    }
+
+    // gaps are filled with the same curve
+    let mut column = k;
+    column[1] = 0.0;
+    column[2] = 0.0;
+    fill_gaps_with(Scheme::LogLinear, &temps, &mut column);
+    assert!((column[1] - k[1]).abs() / k[1] < 1e-5);
+    assert!((column[2] - k[2]).abs() / k[2] < 1e-5);
+}
+
+#[test]
+fn log_linear_stays_linear_for_zero_values() {
+    let temps = [100.0, 200.0];
+    assert_eq!(Scheme::LogLinear.interpolate(&temps, &[0.0, 1.0], 150.0), Some(0.5));
+}
+
+#[test]
+fn scheme_names() {
+    for scheme in [Scheme::Linear, Scheme::MonotoneCubic, Scheme::LogLinear] {
+        assert_eq!(Scheme::parse(scheme.name()), Some(scheme));
+    }
+    assert_eq!(Scheme::parse(" PCHIP"), Some(Scheme::MonotoneCubic));
+    assert_eq!(Scheme::parse("spline"), None);
+}
+
+#[test]
+fn interpolation_header_row() {
+    let path = std::env::temp_dir().join(format!("ro_materials_interpolation_{}.csv", std::process::id()));
+    let read = |header: &str| {
+        std::fs::write(&path, format!("Name,Test,,\n{}\nTemperature,Specific Heat,Thermal Conductivity,Emissivity\n300,900,0.05,0.8\n", header)).unwrap();
+        let mut segment = Segment { path: path.display().to_string(), ..Default::default() };
+        read_material_csv(&mut segment).map(|()| segment.interpolation)
+    };
+
+    assert_eq!(read("Density,144,,").unwrap(), Interpolation::default());
+    assert_eq!(read("Interpolation,log-linear,,").unwrap(), Interpolation::all(Scheme::LogLinear));
+    assert_eq!(read("Interpolation,pchip,log-linear,").unwrap(),
+               Interpolation { specific_heat: Scheme::MonotoneCubic, conductivity: Scheme::LogLinear, emissivity: Scheme::Linear });
+    assert!(matches!(read("Interpolation,spline,,"), Err(CalcError::Parse { line: 2, .. })));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn empty_table() {
+    assert!(fit_list(&PropertyTable::default(), Interpolation::default(), &grid(100.0, 1000.0, 50.0)).is_empty());
+    assert_eq!(locate(&[], 10.0), Position::Below);
+    assert_eq!(interpolate::<f32>(&[], &[], 10.0), None);
+    fill_gaps_in_csv(&mut PropertyTable::default(), Interpolation::default());
+}
+
+#[test]
+fn layer_average_follows_the_scheme() {
+    // sparse rows of an exponentially growing column
+    let data = [300.0, 900.0, 1500.0].into_iter()
+        .map(|temp: f32| (temp, LayerData { thermal_data: Data { cp: 1000.0, R_th: 0.02 * (temp / 400.0).exp(), e: 0.8 }, temp_sub_part: temp }))
+        .collect::<PropertyTable<LayerData>>();
+    let fine = grid(300.0, 1500.0, 5.0);
+
+    let average = |interpolation: Interpolation| avg_cp_k(0.01, &data, 1500.0, 300.0, interpolation, &fine).values()[2].thermal_data.R_th;
+    let linear = average(Interpolation::default());
+    let log_linear = average(Interpolation::all(Scheme::LogLinear));
+    assert!((linear - log_linear).abs() / linear > 1e-3, "{} {}", linear, log_linear);
+    assert_eq!(average(Interpolation { conductivity: Scheme::Linear, ..Interpolation::all(Scheme::LogLinear) }), linear);
+}
@@ -0,0 +1,40 @@
+use ro_materials_csv_calculator::*;
+
+use std::{path::Path, process::Command};
+
+fn segment(name: &str, temp_max: f32, temp_hot_side: f32, temp_cold_side: f32) -> Segment {
+    Segment { name: name.to_string(), temp_max, temp_hot_side, temp_cold_side, ..Default::default() }
+}
+
+#[test]
+fn own_hot_side() {
+    // the metal foil sits on a 1500 K hot side although the layer above ends at 1800 K
+    let tps = TPS {
+        temp: 1920.0,
+        segments_min: vec![segment("RCC", 1920.0, 1920.0, 1800.0), segment("Foil", 1600.0, 1500.0, 1500.0)],
+        ..Default::default()
+    };
+    let margins = temperature_margins(&tps);
+    assert_eq!(margins[1].temp_peak, 1500.0);
+    assert_eq!(margins[1].margin, 100.0);
+    assert_eq!(margins[1].temp_above, 1800.0);
+    assert!(check_margins(&margins, Path::new("Test.csv")).is_empty());
+
+    let hot = TPS { segments_min: vec![segment("Foil", 1600.0, 1700.0, 1500.0)], ..tps };
+    let margins = temperature_margins(&hot);
+    assert_eq!(margins[0].margin, -100.0);
+    assert_eq!(check_margins(&margins, Path::new("Test.csv")).len(), 1);
+}
+
+#[test]
+fn shipped_library_strict() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let out = std::env::temp_dir().join(format!("ro_materials_strict_{}", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_ro_materials_csv_calculator"))
+        .current_dir(root)
+        .args(["--strict", "--out"]).arg(&out).arg("build")
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&out);
+    assert!(output.status.success(), "{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+}
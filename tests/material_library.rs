@@ -0,0 +1,74 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+use std::fs;
+
+/// Material file at `file` inside the library named `name`
+fn material(directory: &TempDir, file: &str, name: &str) {
+    directory.write(file, &format!("Name,{},,\nDensity,144,,\n{}", name, TABLE));
+}
+
+fn library(directory: &TempDir) -> MaterialLibrary {
+    fs::create_dir_all(directory.path().join("materials/sub")).unwrap();
+    fs::create_dir_all(directory.path().join("tps/layers")).unwrap();
+    material(directory, "materials/Alpha.csv", "Alpha");
+    // same name in a second file
+    material(directory, "materials/Alpha_2.csv", "Alpha");
+    material(directory, "materials/Beta.csv", "Beta");
+    material(directory, "materials/sub/Gamma.csv", "Gamma");
+    material(directory, "tps/layers/Delta.csv", "Delta");
+    MaterialLibrary::new(directory.path())
+}
+
+#[test]
+fn resolve_by_name() {
+    let directory = TempDir::new("library_name");
+    let library = library(&directory);
+    let tps = directory.path().join("tps/Tiles.csv");
+    assert_eq!(library.resolve("Beta", &tps).unwrap(), directory.path().join("materials/Beta.csv"));
+    // materials in subfolders are only found by path
+    assert!(matches!(library.resolve("Gamma", &tps), Err(CalcError::MaterialNotFound { .. })));
+}
+
+#[test]
+fn resolve_by_path() {
+    let directory = TempDir::new("library_path");
+    let library = library(&directory);
+    let tps = directory.path().join("tps/Tiles.csv");
+    // relative to the library root
+    assert_eq!(library.resolve("materials/sub/Gamma.csv", &tps).unwrap(), directory.path().join("materials/sub/Gamma.csv"));
+    // relative to the TPS file
+    assert_eq!(library.resolve("layers/Delta.csv", &tps).unwrap(), directory.path().join("tps/layers/Delta.csv"));
+    // a file name alone falls back to materials/
+    assert_eq!(library.resolve("somewhere/Alpha_2.csv", &tps).unwrap(), directory.path().join("materials/Alpha_2.csv"));
+}
+
+#[test]
+fn resolve_ambiguous() {
+    let directory = TempDir::new("library_ambiguous");
+    let library = library(&directory);
+    let tps = directory.path().join("tps/Tiles.csv");
+    // two files named Alpha, neither wins
+    let err = library.resolve("Alpha", &tps).unwrap_err();
+    let files = vec![directory.path().join("materials/Alpha.csv"), directory.path().join("materials/Alpha_2.csv")];
+    assert!(matches!(&err, CalcError::AmbiguousMaterial { reference, files: found, .. } if reference == "Alpha" && *found == files), "{}", err);
+    assert!(err.to_string().contains("refer to one by path"), "{}", err);
+    // by path they resolve
+    assert_eq!(library.resolve("materials/Alpha_2.csv", &tps).unwrap(), files[1]);
+    // a file next to the TPS comes before the one of the same name in materials/
+    material(&directory, "tps/Alpha_2.csv", "Alpha");
+    assert_eq!(library.resolve("Alpha_2.csv", &tps).unwrap(), directory.path().join("tps/Alpha_2.csv"));
+}
+
+#[test]
+fn resolve_not_found() {
+    let directory = TempDir::new("library_missing");
+    let library = library(&directory);
+    let tps = directory.path().join("tps/Tiles.csv");
+    assert!(matches!(library.resolve("Alhpa", &tps), Err(CalcError::MaterialNotFound { suggestion: Some(name), .. }) if name == "Alpha"));
+    assert_eq!(library.suggest("Alhpa"), Some("Alpha"));
+    assert!(matches!(library.resolve("materials/Epsilon.csv", &tps), Err(CalcError::MaterialNotFound { suggestion: None, .. })));
+    assert_eq!(library.suggest("Unobtainium"), None);
+}
@@ -0,0 +1,111 @@
+use ro_materials_csv_calculator::*;
+
+/// Layer with constant properties
+fn layer(name: &str, tickness: f32, density: f32, k: f32, temp_max: f32) -> Segment {
+    let data = Data { cp: 1000.0, R_th: k, e: 0.8 };
+    Segment {
+        name: name.to_string(),
+        path: format!("bib/materials/{}.csv", name),
+        portion: 1.0,
+        tickness,
+        density,
+        temp_max,
+        data_csv: [(100.0, data), (2000.0, data)].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+fn steady(back: BackBoundary) -> LoadCase {
+    LoadCase::Steady { surface_temp: 1000.0, back }
+}
+
+#[test]
+fn layer_limit_sets_the_thickness() {
+    let layers = [layer("insulation", 0.05, 100.0, 0.1, 0.0), layer("structure", 0.01, 2000.0, 0.2, 500.0)];
+    let settings = OptimizeSettings { bounds: vec![ThicknessBounds { layer: 0, min: 0.001, max: 0.1 }], ..Default::default() };
+    let optimum = optimize(&layers, &steady(BackBoundary::Temperature(300.0)), &settings).unwrap();
+
+    // the structure (0.05 m²K/W) stays at 500 K if the insulation has 2.5 times its resistance
+    assert!((optimum.segments[0].tickness - 0.0125).abs() <= 0.0001, "{}", optimum.segments[0].tickness);
+    assert!(optimum.segments[1].temp_hot_side <= 500.0);
+    assert_eq!(optimum.segments[1].tickness, 0.01);
+    assert!((optimum.areal_density - stack_areal_density(&optimum.segments)).abs() < 1e-6);
+}
+
+#[test]
+fn mass_goes_into_the_lighter_layer() {
+    // same conductivity, the back face only sees the sum of both thicknesses
+    let layers = [layer("light", 0.05, 50.0, 0.05, 0.0), layer("heavy", 0.05, 500.0, 0.05, 0.0)];
+    let settings = OptimizeSettings {
+        back_limit: 400.0,
+        bounds: vec![ThicknessBounds { layer: 0, min: 0.002, max: 0.05 }, ThicknessBounds { layer: 1, min: 0.002, max: 0.05 }],
+        resolution: 0.0005,
+    };
+    let optimum = optimize(&layers, &steady(BackBoundary::Convection { temp: 300.0, coefficient: 10.0 }), &settings).unwrap();
+
+    assert_eq!(optimum.segments[1].tickness, 0.002);
+    assert!(optimum.back_face <= 400.0);
+    // the steady back face: 700 K / (1/h + t/k) = 10 W/m²K * 100 K
+    let total = optimum.segments[0].tickness + optimum.segments[1].tickness;
+    assert!((total - 0.03).abs() <= 0.0005 + 1e-6, "{}", total);
+}
+
+#[test]
+fn transient_optimum_is_lighter_and_holds() {
+    let layers = [layer("insulation", 0.03, 150.0, 0.05, 0.0), layer("structure", 0.002, 2800.0, 150.0, 0.0)];
+    let flux = Surface::HeatFlux([(0.0, 20000.0), (200.0, 20000.0), (201.0, 0.0), (600.0, 0.0)].into_iter().collect());
+    let case = LoadCase::Transient { surface: flux.clone(), settings: TransientSettings { time_step: 2.0, cells_per_layer: 4, ..Default::default() } };
+    let settings = OptimizeSettings { back_limit: 400.0, bounds: vec![ThicknessBounds { layer: 0, min: 0.002, max: 0.03 }], resolution: 0.0005 };
+    let optimum = optimize(&layers, &case, &settings).unwrap();
+
+    assert!(optimum.areal_density < stack_areal_density(&layers));
+    assert!(optimum.back_face <= 400.0);
+    // one step thinner fails
+    let mut thinner = optimum.segments.clone();
+    thinner[0].tickness -= 0.0005;
+    let LoadCase::Transient { settings, .. } = &case else { unreachable!() };
+    let transient = simulate(&thinner, &flux, settings).unwrap();
+    assert!(transient.peak_back_face().back_face() > 400.0);
+}
+
+#[test]
+fn optimize_errors() {
+    let layers = [layer("insulation", 0.05, 100.0, 0.1, 0.0), layer("structure", 0.01, 2000.0, 0.2, 500.0)];
+    let case = steady(BackBoundary::Temperature(300.0));
+    let bounds = |layer, min, max| OptimizeSettings { bounds: vec![ThicknessBounds { layer, min, max }], ..Default::default() };
+
+    assert!(matches!(optimize(&layers, &case, &OptimizeSettings::default()), Err(CalcError::Optimization(_))));
+    assert!(matches!(optimize(&layers, &case, &bounds(2, 0.001, 0.1)), Err(CalcError::Optimization(_))));
+    assert!(matches!(optimize(&layers, &case, &bounds(0, 0.1, 0.001)), Err(CalcError::Optimization(_))));
+    let err = optimize(&layers, &case, &bounds(0, 0.001, 0.005)).unwrap_err();
+    assert!(err.to_string().contains("structure reaches"), "{}", err);
+}
+
+#[test]
+fn tps_csv_round_trip() {
+    let directory = std::env::temp_dir().join(format!("ro_materials_optimize_{}", std::process::id()));
+    let mut top = layer("RCG", 0.000254, 1900.0, 1.0, 1700.0);
+    top.top_layer = true;
+    top.temp_hot_side = 1590.0;
+    top.temp_cold_side = 1590.0;
+    let spec = TpsSpec {
+        name: "Stack_Optimized".to_string(),
+        temp: 1590.0,
+        back_boundary: Some(BackBoundary::Convection { temp: 300.0, coefficient: 12.5 }),
+        segments_min: vec![top.clone(), layer("LI-900", 0.0433, 144.0, 0.05, 1590.0)],
+        segments_max: vec![top, layer("LI-900", 0.1, 144.0, 0.05, 1590.0)],
+        ..Default::default()
+    };
+    let path = output_tps_csv(&spec, &directory).unwrap();
+    let read = read_tps_csv(&path).unwrap();
+
+    assert_eq!(read.name, spec.name);
+    assert_eq!(read.temp, 1590.0);
+    assert_eq!(read.back_boundary, spec.back_boundary);
+    assert!(read.unknown_keys.is_empty());
+    for (read, written) in read.segments_min.iter().chain(read.segments_max.iter()).zip(spec.segments_min.iter().chain(spec.segments_max.iter())) {
+        assert_eq!((&read.path, read.top_layer, read.tickness, read.portion), (&written.path, written.top_layer, written.tickness, written.portion));
+        assert_eq!((read.temp_hot_side, read.temp_cold_side), (written.temp_hot_side, written.temp_cold_side));
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
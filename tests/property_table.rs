@@ -0,0 +1,57 @@
+use ro_materials_csv_calculator::*;
+
+fn table() -> PropertyTable<f32> {
+    // unsorted on purpose
+    [(300.0, 3.0), (100.0, 1.0), (200.0, 1.0)].into_iter().collect()
+}
+
+#[test]
+fn rows_get_sorted() {
+    let table = table();
+    assert_eq!(table.temps(), &[100.0, 200.0, 300.0]);
+    assert_eq!(table.values(), &[1.0, 1.0, 3.0]);
+}
+
+#[test]
+fn at_inside_and_outside() {
+    let table = table();
+    assert_eq!(table.at(200.0).unwrap(), 1.0);
+    assert_eq!(table.at(250.0).unwrap(), 2.0);
+
+    assert_eq!(table.at(0.0).unwrap(), 1.0);
+    assert_eq!(table.at(400.0).unwrap(), 3.0);
+
+    let linear = table.clone().with_extrapolation(Extrapolation::Linear);
+    assert_eq!(linear.at(0.0).unwrap(), 1.0);
+    assert_eq!(linear.at(400.0).unwrap(), 5.0);
+
+    let strict = table.with_extrapolation(Extrapolation::Error);
+    assert!(matches!(strict.at(400.0), Err(CalcError::OutOfRange { .. })));
+    assert!(matches!(PropertyTable::<f32>::default().at(0.0), Err(CalcError::EmptyTable)));
+}
+
+#[test]
+fn resample_onto_grid() {
+    let resampled = table().resample(&[50.0, 150.0, 250.0, 350.0]).unwrap();
+    assert_eq!(resampled.values(), &[1.0, 1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn integrate_piecewise_linear() {
+    let table = table();
+    assert_eq!(table.integrate(100.0, 300.0, |value| *value).unwrap(), 300.0);
+    assert_eq!(table.integrate(150.0, 250.0, |value| *value).unwrap(), 125.0);
+    assert_eq!(table.integrate(300.0, 100.0, |value| *value).unwrap(), -300.0);
+    // clamped outside of the rows
+    assert_eq!(table.integrate(0.0, 100.0, |value| *value).unwrap(), 100.0);
+}
+
+#[test]
+fn arithmetic() {
+    let table = table();
+    let other = [(100.0, 1.0), (300.0, 3.0)].into_iter().collect::<PropertyTable<f32>>();
+
+    assert_eq!((&table + &other).unwrap().values(), &[2.0, 3.0, 6.0]);
+    assert_eq!((&table - &other).unwrap().values(), &[0.0, -1.0, 0.0]);
+    assert_eq!((&table * 2.0).values(), &[2.0, 2.0, 6.0]);
+}
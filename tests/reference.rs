@@ -0,0 +1,58 @@
+use ro_materials_csv_calculator::*;
+
+fn coarse_tps() -> TPS {
+    // rows 200 K apart, none within 25 K of the max temperature
+    let table = [(300.0, 0.6), (500.0, 0.7), (700.0, 0.8), (900.0, 0.9)].into_iter()
+        .map(|(temp, e)| (temp, Data { cp: temp, R_th: 1.0, e }))
+        .collect::<PropertyTable<Data>>();
+    TPS { name: "Coarse".to_string(), temp: 800.0, data_min: table.clone(), data_max: table, ..Default::default() }
+}
+
+/// Surface temperature `rows` of (time, temperature)
+fn pulse(rows: &[(f32, f32)]) -> ReferenceTemp {
+    ReferenceTemp::Pulse(HeatPulse { source: "reentry.csv".to_string(), history: rows.iter().copied().collect() })
+}
+
+#[test]
+fn parse_reference() {
+    assert_eq!(ReferenceTemp::parse("max"), Some(ReferenceTemp::Max));
+    assert_eq!(ReferenceTemp::parse("Radiative"), None);
+    assert_eq!(ReferenceTemp::parse("0.8"), Some(ReferenceTemp::Fraction(0.8)));
+    assert_eq!(ReferenceTemp::parse("1.5"), None);
+    assert_eq!(ReferenceTemp::parse("0"), None);
+    assert_eq!(ReferenceTemp::parse("pulse"), None);
+}
+
+#[test]
+fn reference_values() {
+    let curve = [(300.0, 300.0), (900.0, 900.0)].into_iter().collect::<PropertyTable<f32>>();
+    assert_eq!(ReferenceTemp::Max.value(&curve, 800.0), 800.0);
+    assert_eq!(ReferenceTemp::Fraction(0.5).value(&curve, 800.0), 400.0);
+    // 10 s at 400 K, 10 s at 800 K, the hot half radiates 16 times the heat
+    let steps = pulse(&[(0.0, 400.0), (10.0, 400.0), (10.0, 800.0), (20.0, 800.0)]);
+    let value = steps.value(&curve, 800.0);
+    assert!((value - (400.0 + 800.0 * 16.0) / 17.0).abs() < 0.01, "{}", value);
+    // longer at the cold end pulls the mean down
+    let long_cold = pulse(&[(0.0, 400.0), (160.0, 400.0), (160.0, 800.0), (170.0, 800.0)]);
+    assert!((long_cold.value(&curve, 800.0) - 600.0).abs() < 0.01);
+    // above skinMaxTemp it counts as skinMaxTemp
+    assert!((steps.value(&curve, 600.0) - (400.0 + 600.0 * 81.0 / 16.0) / (1.0 + 81.0 / 16.0)).abs() < 0.01);
+    assert_eq!(ReferenceTemp::Max.value(&PropertyTable::default(), 800.0), 0.0);
+}
+
+#[test]
+fn coarse_grid() {
+    let preset = Preset::from_tps(&coarse_tps(), ReferenceTemp::Max);
+    let node = preset.to_node();
+    // between the 700 K & 900 K rows, not the 300 K row
+    assert_eq!(node.get("emissiveConstant"), Some("0.85"));
+    assert_eq!(node.get("skinSpecificHeatCapacity"), Some("800"));
+    assert_eq!(preset.comments[0], "Values at 800 K, skinMaxTemp");
+
+    let preset = Preset::from_tps(&coarse_tps(), ReferenceTemp::Fraction(0.75));
+    assert_eq!(preset.to_node().get("skinSpecificHeatCapacity"), Some("600"));
+    assert_eq!(preset.comments[0], "Values at 600 K, 0.75 of skinMaxTemp");
+
+    let preset = Preset::from_tps(&coarse_tps(), pulse(&[(0.0, 300.0), (100.0, 900.0)]));
+    assert_eq!(preset.comments[0], "Values averaged over the heat pulse reentry.csv up to 800 K, weighted by T^4 & time");
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+/// Layer with a conductivity of `k0 + k1 * T`
+fn layer(tickness: f32, k0: f32, k1: f32) -> Segment {
+    let data = |temp: f32| Data { cp: 1000.0, R_th: k0 + k1 * temp, e: 0.8 };
+    Segment {
+        portion: 1.0,
+        tickness,
+        data_csv: [(100.0, data(100.0)), (2000.0, data(2000.0))].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn constant_conductivity_is_linear() {
+    let layers = [layer(0.02, 0.1, 0.0), layer(0.01, 0.2, 0.0)];
+    let profile = steady_profile(&layers, 1000.0, BackBoundary::Temperature(300.0)).unwrap();
+
+    // resistances 0.2 & 0.05 m²K/W in series
+    assert!((profile.flux - 700.0 / 0.25).abs() < 0.5, "{}", profile.flux);
+    assert!((profile.faces[1] - (1000.0 - 700.0 * 0.2 / 0.25)).abs() < 0.1, "{:?}", profile.faces);
+    assert!((profile.faces[2] - 300.0).abs() < 0.01);
+}
+
+#[test]
+fn conductivity_follows_temperature() {
+    let layers = [layer(0.05, 0.02, 1e-4)];
+    let profile = steady_profile(&layers, 1500.0, BackBoundary::Temperature(400.0)).unwrap();
+
+    // ∫ k dT over the layer equals flux times thickness
+    let conducted = 0.02 * 1100.0 + 1e-4 / 2.0 * (1500.0_f32.powi(2) - 400.0_f32.powi(2));
+    assert!((profile.flux * 0.05 - conducted).abs() / conducted < 1e-3, "{}", profile.flux);
+}
+
+#[test]
+fn back_face_radiates_and_convects() {
+    let layers = [layer(0.0003, 1.0, 0.0), layer(0.05, 0.05, 5e-5), layer(0.002, 150.0, 0.0)];
+    for back in [BackBoundary::Radiation { temp: 300.0, emissivity: 0.9 }, BackBoundary::Convection { temp: 300.0, coefficient: 10.0 }] {
+        let profile = steady_profile(&layers, 1400.0, back).unwrap();
+        let back_temp = *profile.faces.last().unwrap();
+        let passed_on = match back {
+            BackBoundary::Radiation { temp, emissivity } => emissivity * transient::STEFAN_BOLTZMANN * (back_temp.powi(4) - temp.powi(4)),
+            BackBoundary::Convection { temp, coefficient } => coefficient * (back_temp - temp),
+            BackBoundary::Temperature(_) => unreachable!(),
+        };
+
+        assert!((profile.flux - passed_on).abs() / profile.flux < 1e-3, "{:?}: {} / {}", back, profile.flux, passed_on);
+        assert!(back_temp > 300.0 && back_temp < 1400.0);
+        assert!(profile.faces.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", profile.faces);
+    }
+}
+
+#[test]
+fn apply_sets_hot_and_cold_sides() {
+    let mut layers = [layer(0.02, 0.1, 0.0), layer(0.01, 0.2, 0.0)];
+    apply_steady_profile(&mut layers, 1000.0, BackBoundary::Temperature(300.0)).unwrap();
+
+    assert_eq!(layers[0].temp_hot_side, 1000.0);
+    assert_eq!(layers[0].temp_cold_side, layers[1].temp_hot_side);
+    assert!((layers[1].temp_cold_side - 300.0).abs() < 0.01);
+}
+
+#[test]
+fn invalid_back_face() {
+    let layers = [layer(0.02, 0.1, 0.0)];
+    assert!(steady_profile(&layers, 1000.0, BackBoundary::Temperature(1200.0)).is_err());
+    assert!(steady_profile(&layers, 1000.0, BackBoundary::Radiation { temp: 300.0, emissivity: 0.0 }).is_err());
+    assert!(steady_profile(&layers, 1000.0, BackBoundary::Convection { temp: 300.0, coefficient: -1.0 }).is_err());
+}
+
+#[test]
+fn back_face_row_replaces_hand_entered_temperatures() {
+    let library = MaterialLibrary::new(&library_root());
+    let directory = TempDir::new("steady");
+    let path = directory.write("LI-900.csv", "Name,LI-900 steady,,,,\nTemperature,1590,,,,\nBack Face,Radiation,300,0.8,,\n\
+        Min,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\n\
+        Top Layer,RCG,1,0.000254,,\nLayer,LI-900,1,0.0254,,\nLayer,SIP,1,0.002,,\nLayer,RTV-560,1.8,0.00019,,\n\
+        Max,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\n\
+        Top Layer,RCG,1,0.000254,,\nLayer,LI-900,1,0.127,,\nLayer,SIP,1,0.002,,\nLayer,RTV-560,1.8,0.00019,,\n");
+
+    let spec = load_tps_spec(&path, &library).unwrap();
+    assert_eq!(spec.back_boundary, Some(BackBoundary::Radiation { temp: 300.0, emissivity: 0.8 }));
+    // a thin tile soaked at 1590 K overheats the bond, only warnings
+    assert!(validate_tps(&spec).iter().all(|issue| issue.severity == Severity::Warning), "{:?}", validate_tps(&spec));
+    for segments in [&spec.segments_min, &spec.segments_max] {
+        assert_eq!(segments[0].temp_hot_side, 1590.0);
+        assert!(segments.windows(2).all(|pair| pair[0].temp_cold_side == pair[1].temp_hot_side));
+        // the tile takes nearly all of the drop
+        assert!(segments[1].temp_hot_side - segments[1].temp_cold_side > 800.0, "{:?}", segments[1].temp_cold_side);
+    }
+    assert!(spec.segments_max[3].temp_cold_side < spec.segments_min[3].temp_cold_side);
+
+    directory.write("LI-900.csv", "Name,LI-900,,,,\nTemperature,1590,,,,\nMin,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\n\
+        Top Layer,RCG,1,0.000254,,\nLayer,LI-900,1,0.0254,,\n\
+        Max,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\nTop Layer,RCG,1,0.000254,1590,1590\nLayer,LI-900,1,0.127,1590,440\n");
+    let spec = load_tps_spec(&path, &library).unwrap();
+    assert!(validate_tps(&spec).iter().any(|issue| issue.message.contains("hot side is missing")));
+}
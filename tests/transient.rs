@@ -0,0 +1,133 @@
+use ro_materials_csv_calculator::*;
+
+/// Layer with constant properties
+fn layer(name: &str, tickness: f32, density: f32, cp: f32, k: f32, e: f32) -> Segment {
+    Segment {
+        name: name.to_string(),
+        portion: 1.0,
+        tickness,
+        density,
+        data_csv: [(100.0, Data { cp, R_th: k, e }), (2000.0, Data { cp, R_th: k, e })].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+fn history(rows: &[(f32, f32)]) -> PropertyTable<f32> {
+    rows.iter().copied().collect()
+}
+
+#[test]
+fn absorbed_heat_is_stored() {
+    // no emissivity, no radiation: all of the flux ends up in the layers
+    let layers = [layer("a", 0.01, 100.0, 1000.0, 20.0, 0.0), layer("b", 0.002, 2000.0, 800.0, 50.0, 0.0)];
+    let flux = Surface::HeatFlux(history(&[(0.0, 5000.0), (100.0, 5000.0)]));
+    let settings = TransientSettings { time_step: 0.5, ..Default::default() };
+    let transient = simulate(&layers, &flux, &settings).unwrap();
+
+    assert_eq!(transient.points.len(), 201);
+    assert_eq!(transient.layers, ["a", "b"]);
+    let last = transient.points.last().unwrap();
+    assert_eq!(last.time, 100.0);
+
+    // conductive enough for a nearly linear profile inside each layer
+    let stored = (last.faces[0] + last.faces[1] - 2.0 * 300.0) / 2.0 * 100.0 * 1000.0 * 0.01
+        + (last.faces[1] + last.faces[2] - 2.0 * 300.0) / 2.0 * 2000.0 * 800.0 * 0.002;
+    assert!((stored - 5000.0 * 100.0).abs() / 5e5 < 0.01, "{}", stored);
+    assert!(last.faces.windows(2).all(|pair| pair[0] >= pair[1]));
+}
+
+#[test]
+fn steady_state_through_two_layers() {
+    let layers = [layer("a", 0.02, 100.0, 1000.0, 0.1, 0.8), layer("b", 0.01, 100.0, 1000.0, 0.2, 0.8)];
+    let surface = Surface::Temperature(history(&[(0.0, 1000.0)]));
+    let settings = TransientSettings { time_step: 50.0, duration: 50000.0, back_face: BackFace::Temperature(300.0), ..Default::default() };
+    let last = simulate(&layers, &surface, &settings).unwrap().points.pop().unwrap();
+
+    // resistances 0.2 & 0.05 m²K/W in series
+    let interface = 1000.0 - 700.0 * 0.2 / 0.25;
+    assert_eq!(last.surface(), 1000.0);
+    assert_eq!(last.back_face(), 300.0);
+    assert!((last.faces[1] - interface).abs() < 0.5, "{}", last.faces[1]);
+}
+
+#[test]
+fn insulated_stack_heats_up_evenly() {
+    let layers = [layer("a", 0.01, 100.0, 1000.0, 0.5, 0.8)];
+    let surface = Surface::Temperature(history(&[(0.0, 300.0), (10.0, 800.0)]));
+    let settings = TransientSettings { time_step: 10.0, duration: 20000.0, ..Default::default() };
+    let transient = simulate(&layers, &surface, &settings).unwrap();
+
+    assert!((transient.points.last().unwrap().back_face() - 800.0).abs() < 0.1);
+    assert!(transient.points.windows(2).all(|pair| pair[0].back_face() <= pair[1].back_face() + 1e-3));
+    assert_eq!(transient.peak_back_face().time, transient.points.last().unwrap().time);
+}
+
+#[test]
+fn layers_without_thickness_are_left_out() {
+    let layers = [layer("a", 0.01, 100.0, 1000.0, 0.5, 0.8), layer("glue", 0.0, 1000.0, 1000.0, 0.5, 0.8)];
+    let surface = Surface::Temperature(history(&[(0.0, 500.0)]));
+    let transient = simulate(&layers, &surface, &TransientSettings::default()).unwrap();
+    assert_eq!(transient.layers, ["a"]);
+    assert_eq!(transient.points[0].faces.len(), 2);
+
+    assert!(matches!(simulate(&layers[1..], &surface, &TransientSettings::default()), Err(CalcError::Simulation(_))));
+}
+
+#[test]
+fn skin_model_from_preset() {
+    let preset = PresetData {
+        emissive_const: Some(0.9),
+        areal_density_min: Some(4.7),
+        cp_min: Some(1100.0),
+        insulance_min: Some(250.0),
+        ..Default::default()
+    };
+    let skin = SkinModel::from_preset(&preset, false).unwrap();
+    assert_eq!(skin.insulance, 0.25);
+    assert_eq!(skin.mass_per_area, 4.7);
+    assert!(SkinModel::from_preset(&preset, true).is_none());
+}
+
+#[test]
+fn skin_model_back_face_follows_exponential() {
+    let skin = SkinModel { mass_per_area: 1.0, specific_heat: 1000.0, insulance: 0.1, emissivity: 0.8 };
+    let surface = Surface::Temperature(history(&[(0.0, 1000.0)]));
+    let settings = TransientSettings { time_step: 0.1, duration: 200.0, back_face: BackFace::HeatSink(1000.0), ..Default::default() };
+    let transient = simulate_skin(&skin, &surface, &settings).unwrap();
+
+    // time constant R·C = 100 s
+    let last = transient.points.last().unwrap();
+    let expected = 1000.0 - 700.0 * (-2.0f32).exp();
+    assert!((last.back_face() - expected).abs() < 1.0, "{} != {}", last.back_face(), expected);
+}
+
+#[test]
+fn skin_model_back_face_depends_on_insulance() {
+    let surface = Surface::Temperature(history(&[(0.0, 1000.0)]));
+    let settings = TransientSettings { time_step: 0.5, duration: 100.0, back_face: BackFace::HeatSink(5000.0), ..Default::default() };
+    let back_face = |insulance: f32| {
+        let skin = SkinModel { mass_per_area: 1.0, specific_heat: 1000.0, insulance, emissivity: 0.8 };
+        simulate_skin(&skin, &surface, &settings).unwrap().points.last().unwrap().back_face()
+    };
+    assert!(back_face(0.05) > back_face(0.5) + 50.0, "{} {}", back_face(0.05), back_face(0.5));
+
+    // nothing behind an adiabatic back face to hold it below the skin
+    let adiabatic = TransientSettings { back_face: BackFace::Adiabatic, ..settings };
+    let skin = SkinModel { mass_per_area: 1.0, specific_heat: 1000.0, insulance: 0.1, emissivity: 0.8 };
+    assert!(matches!(simulate_skin(&skin, &surface, &adiabatic), Err(CalcError::Simulation(_))));
+}
+
+#[test]
+fn thin_layer_matches_skin_model() {
+    // little capacity in the insulation, so lumping it into the skin is exact enough
+    let layers = [layer("skin", 0.001, 2000.0, 1000.0, 50.0, 0.8), layer("insulation", 0.01, 2.0, 1000.0, 0.1, 0.8)];
+    let skin = SkinModel { mass_per_area: 2.02, specific_heat: 1000.0, insulance: 0.001 / 50.0 + 0.1, emissivity: 0.8 };
+    let flux = Surface::HeatFlux(history(&[(0.0, 0.0), (100.0, 50000.0), (300.0, 0.0)]));
+    let settings = TransientSettings { time_step: 0.5, back_face: BackFace::HeatSink(5000.0), ..Default::default() };
+
+    let multilayer = simulate(&layers, &flux, &settings).unwrap();
+    let errors = compare(&multilayer, &simulate_skin(&skin, &flux, &settings).unwrap());
+    assert_eq!(errors.len(), multilayer.points.len());
+    assert!(errors.iter().all(|error| error.surface.abs() < 5.0 && error.back_face.abs() < 5.0),
+            "{:?}", errors.iter().max_by(|a, b| a.surface.abs().total_cmp(&b.surface.abs())));
+}
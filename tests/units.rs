@@ -0,0 +1,89 @@
+use ro_materials_csv_calculator::*;
+use ro_materials_csv_calculator::units::*;
+
+fn assert_close(value: f32, expected: f32) {
+    assert!((value - expected).abs() <= expected.abs() * 1e-5, "{} != {}", value, expected);
+}
+
+fn read_material(name: &str, content: &str) -> Result<Segment, CalcError> {
+    let path = std::env::temp_dir().join(format!("ro_materials_units_{}_{}.csv", name, std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    let mut segment = Segment { path: path.display().to_string(), ..Default::default() };
+    let result = read_material_csv(&mut segment);
+    std::fs::remove_file(&path).unwrap();
+    result.map(|()| segment)
+}
+
+#[test]
+fn temperature_units() {
+    assert_close(TemperatureUnit::parse("°F").unwrap().to_kelvin(2300.0), 1533.15);
+    assert_close(TemperatureUnit::parse("C").unwrap().to_kelvin(25.0), 298.15);
+    assert_close(TemperatureUnit::parse("°R").unwrap().to_kelvin(900.0), 500.0);
+    assert_eq!(TemperatureUnit::parse("K").unwrap().to_kelvin(300.0), 300.0);
+    assert_eq!(TemperatureUnit::parse("kg"), None);
+}
+
+#[test]
+fn unit_spellings() {
+    assert_eq!(specific_heat_factor("J/kgK"), Some(1.0));
+    assert_eq!(specific_heat_factor("J/kg·K"), specific_heat_factor("j/kg-k"));
+    assert_eq!(specific_heat_factor("BTU/lb·°F"), Some(4186.8));
+    assert_eq!(conductivity_factor("BTU·in/hr·ft²·°F"), conductivity_factor("btu in/hr ft^2 F"));
+    assert_close(conductivity_factor("BTU/hr·ft·°F").unwrap() / conductivity_factor("BTU·in/hr·ft²·°F").unwrap(), 12.0);
+    assert_eq!(conductivity_factor("J/kgK"), None);
+    assert!(is_dimensionless("-") && is_dimensionless("") && !is_dimensionless("W/mK"));
+}
+
+#[test]
+fn files_without_units_row_are_si() {
+    let segment = read_material("si", "Name,Test,,\nTemperature Limit,1590,,\nTemperature,Specific Heat,Thermal Conductivity,Emissivity\n300,900,0.05,0.8\n").unwrap();
+    assert_eq!(segment.temp_max, 1590.0);
+    assert_eq!(segment.data_csv.temps(), &[300.0]);
+    assert_eq!(segment.data_csv.values()[0].cp, 900.0);
+}
+
+#[test]
+fn imperial_table_is_converted() {
+    let segment = read_material("imperial", "Name,Test,,,\nTemperature Limit,2300,,,\nUnits,°F,BTU/lb·°F,BTU·in/hr·ft²·°F,-\n\
+        Temperature,Specific Heat,Thermal Conductivity,Emissivity\n80,0.2,0.3,0.85\n2300,,0.9,\n").unwrap();
+
+    assert_close(segment.temp_max, 1533.15);
+    assert_close(segment.data_csv.temps()[0], 299.8167);
+    let row = segment.data_csv.values()[0];
+    assert_close(row.cp, 837.36);
+    assert_close(row.R_th, 0.3 * 0.144_227_9);
+    assert_eq!(row.e, 0.85);
+    // gaps stay gaps
+    assert_eq!(segment.data_csv.values()[1].cp, 0.0);
+}
+
+#[test]
+fn unknown_unit() {
+    let result = read_material("unknown", "Name,Test,,\nUnits,K,kcal,W/mK,-\nTemperature,Specific Heat,Thermal Conductivity,Emissivity\n300,900,0.05,0.8\n");
+    assert!(matches!(result, Err(CalcError::Parse { line: 2, column, .. }) if column == "Specific Heat unit"));
+}
+
+#[test]
+fn empty_temperature_cells_stay_empty() {
+    for unit in ["°C", "°F"] {
+        let segment = read_material("empty_temp", &format!("Name,Test,,,\nUnits,{},J/kgK,W/mK,-\n\
+            Temperature,Specific Heat,Thermal Conductivity,Emissivity\n100,900,0.05,0.8\n,950,0.06,0.8\n", unit)).unwrap();
+        assert_eq!(segment.data_csv.temps()[0], 0.0, "{}", unit);
+        assert!(segment.data_csv.temps()[1] > 273.0, "{}", unit);
+        // no Temperature Limit row, no limit
+        assert_eq!(segment.temp_max, 0.0, "{}", unit);
+    }
+}
+
+#[test]
+fn zero_degrees_are_converted() {
+    let segment = read_material("zero_celsius", "Name,Test,,,\nTemperature Limit,0,,,\nUnits,°C,J/kgK,W/mK,-\n\
+        Temperature,Specific Heat,Thermal Conductivity,Emissivity\n0,900,0.05,0.8\n100,950,0.06,0.8\n").unwrap();
+    assert_close(segment.data_csv.temps()[0], 273.15);
+    assert_close(segment.data_csv.temps()[1], 373.15);
+    assert_close(segment.temp_max, 273.15);
+
+    let segment = read_material("zero_fahrenheit", "Name,Test,,,\nUnits,°F,J/kgK,W/mK,-\n\
+        Temperature,Specific Heat,Thermal Conductivity,Emissivity\n0,900,0.05,0.8\n").unwrap();
+    assert_close(segment.data_csv.temps()[0], 255.372_2);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::*;
+use ro_materials_csv_calculator::*;
+
+const MIN: &str = "Min,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\n";
+const MAX: &str = "Max,Path,Portion,Tickness,Temp Hot Side, Temp Cold Side\n";
+
+fn load(directory: &TempDir, content: &str) -> Result<TpsSpec, Vec<CalcError>> {
+    let path = directory.write("Tiles.csv", &format!("Name,Tiles,,,,\nTemperature,1500,,,,\n{}", content));
+    load_tps_spec(&path, &MaterialLibrary::new(&library_root()))
+}
+
+fn messages(spec: &TpsSpec, severity: Severity) -> Vec<String> {
+    validate_tps(spec).into_iter().filter(|issue| issue.severity == severity).map(|issue| issue.message).collect()
+}
+
+#[test]
+fn misspelled_material() {
+    let directory = TempDir::new("validate_material");
+    let errors = load(&directory, &format!("{}Top Layer,LI-9000,1,0.01,1500,500\n{}Top Layer,LI-900,1,0.02,1500,400\n", MIN, MAX)).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], CalcError::MaterialNotFound { reference, suggestion: Some(name), .. } if reference == "LI-9000" && name == "LI-900"));
+    assert!(errors[0].to_string().ends_with("material \"LI-9000\" not found, did you mean \"LI-900\"?"), "{}", errors[0]);
+
+    let errors = load(&directory, &format!("{}Top Layer,Unobtainium,1,0.01,1500,500\n{}Top Layer,LI-900,1,0.02,1500,400\n", MIN, MAX)).unwrap_err();
+    assert!(matches!(&errors[0], CalcError::MaterialNotFound { suggestion: None, .. }));
+}
+
+#[test]
+fn misspelled_key() {
+    let directory = TempDir::new("validate_key");
+    let spec = load(&directory, &format!("Descripton,tiles,,,,\n{}Top Layer,LI-900,1,0.01,1500,500\n{}Top Layer,LI-900,1,0.02,1500,400\n", MIN, MAX)).unwrap();
+    let issues = validate_tps(&spec);
+    assert_eq!(issues.len(), 1, "{:?}", issues);
+    assert_eq!((issues[0].severity, issues[0].line), (Severity::Warning, Some(3)));
+    assert_eq!(issues[0].message, "unknown key \"Descripton\", did you mean \"Description\"?");
+}
+
+#[test]
+fn missing_sections() {
+    let directory = TempDir::new("validate_sections");
+    // Min-only files use their Min layers for Max too
+    let spec = load(&directory, &format!("{}Top Layer,LI-900,1,0.01,1500,500\n", MIN)).unwrap();
+    assert!(messages(&spec, Severity::Error).is_empty());
+    assert_eq!(messages(&spec, Severity::Warning), ["Max section has no layers, the Min layers are used"]);
+    let tps = compute_tps(&spec, &temp_grid());
+    assert_eq!(tps.tickness_max, tps.tickness_min);
+    assert_eq!(tps.data_max.values(), tps.data_min.values());
+
+    let spec = load(&directory, &format!("{}Top Layer,LI-900,1,0.02,1500,400\n", MAX)).unwrap();
+    assert_eq!(messages(&spec, Severity::Error), ["Min section has no layers"]);
+
+    let spec = load(&directory, &format!("{}Layer,LI-900,1,0.01,1500,500\n{}Top Layer,LI-900,1,0.02,1500,400\n", MIN, MAX)).unwrap();
+    assert_eq!(messages(&spec, Severity::Error), ["Min section lacks a \"Top Layer\""]);
+}
+
+#[test]
+fn severities() {
+    let directory = TempDir::new("validate_severities");
+    let spec = load(&directory, &format!("{}Top Layer,LI-900,1,0.01,1500,500\nLayer,SIP,1,0,500,450\n\
+        {}Top Layer,LI-900,1,0.02,1500,400\nLayer,RTV-560,1,0.001,400,350\n", MIN, MAX)).unwrap();
+    assert!(messages(&spec, Severity::Error).is_empty());
+    let warnings = messages(&spec, Severity::Warning);
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(warnings[0].starts_with("Min layer 2 (SIP): thickness is 0 m"));
+    assert!(warnings[1].starts_with("Min and Max use different materials"));
+
+    let spec = load(&directory, &format!("{}Top Layer,LI-900,1,-0.01,1500,500\nLayer,SIP,0,0.002,400,450\n\
+        {}Top Layer,LI-900,1,0.02,1500,400\nLayer,SIP,1,0.002,400,350\n", MIN, MAX)).unwrap();
+    assert_eq!(messages(&spec, Severity::Error), [
+        "Min layer 1 (LI-900): thickness -0.01 m is negative",
+        "Min layer 2 (SIP): portion 0 is outside (0, ∞)",
+        "Min layer 2 (SIP): hot side 400 K is colder than cold side 450 K",
+    ]);
+}